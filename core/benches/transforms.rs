@@ -0,0 +1,93 @@
+//! Transform propagation on a scene with tens of thousands of transforms, run with
+//! `cargo bench -p fatum --bench transforms`.
+
+use std::{hint::black_box, time::{Duration, Instant}};
+
+use fatum::components::{self, Transform3D};
+use fatum_scene::{Node, NodeId, SceneGraph};
+use glam::{Quat, Vec3};
+
+const BRANCHES: usize = 64;
+const CHILDREN: usize = 32;
+const LEAVES: usize = 16;
+
+fn transform(x: f32) -> Box<Transform3D> {
+	Box::new(Transform3D::new(Vec3::new(x, 1.0, 0.0), Quat::from_rotation_y(0.1), Vec3::ONE))
+}
+
+/// Branches with children with leaves, all with a transform. Returns the branches.
+fn build(graph: &mut SceneGraph) -> Vec<NodeId> {
+	let mut branches = Vec::new();
+
+	for i in 0..BRANCHES {
+		let mut branch = Node::with_name("Branch");
+		branch.add_component(transform(i as f32));
+		let branch = graph.add_node(branch, None);
+
+		for _ in 0..CHILDREN {
+			let mut child = Node::with_name("Child");
+			child.add_component(transform(1.0));
+			let child = graph.add_node(child, Some(branch));
+
+			for _ in 0..LEAVES {
+				let mut leaf = Node::with_name("Leaf");
+				leaf.add_component(transform(2.0));
+				graph.add_node(leaf, Some(child));
+			}
+		}
+
+		branches.push(branch);
+	}
+
+	branches
+}
+
+fn bench<F: FnMut()>(name: &str, mut f: F) {
+	// warm up the thread pool and caches
+	for _ in 0..3 {
+		f();
+	}
+
+	let mut iterations = 0;
+	let start = Instant::now();
+
+	while start.elapsed() < Duration::from_secs(2) {
+		f();
+		iterations += 1;
+	}
+
+	println!("{:<24} {:>10.3?} / iteration", name, start.elapsed() / iterations);
+}
+
+fn main() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+	let branches = build(&mut graph);
+
+	println!("{} transforms on {} threads", BRANCHES * (1 + CHILDREN * (1 + LEAVES)), rayon::current_num_threads());
+
+	bench("all moved", || {
+		for branch in &branches {
+			graph.node_mut(*branch).unwrap().component_mut::<Transform3D>().unwrap().translate(Vec3::X);
+		}
+
+		black_box(components::propagate_transforms(&mut graph));
+	});
+
+	bench("one branch moved", || {
+		graph.node_mut(branches[0]).unwrap().component_mut::<Transform3D>().unwrap().translate(Vec3::X);
+		black_box(components::propagate_transforms(&mut graph));
+	});
+
+	bench("one branch reparented", || {
+		let (first, last) = (branches[0], branches[BRANCHES - 1]);
+		let parent = if graph.parent(first) == last { graph.root_id() } else { last };
+
+		graph.reparent(first, parent);
+		black_box(components::propagate_transforms(&mut graph));
+	});
+
+	bench("nothing moved", || {
+		black_box(components::propagate_transforms(&mut graph));
+	});
+}
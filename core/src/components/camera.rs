@@ -1,12 +1,17 @@
-use fatum_scene::{Node, NodeComponent, NodeId, SharedSceneGraph};
+use fatum_scene::{Node, NodeComponent, NodeId, Reflect, SharedSceneGraph};
 use glam::{Mat3, Mat4, Quat, UVec2, Vec3};
+use serde::{Deserialize, Serialize};
 
 use crate::{components::{Transform, Transform3D}, helpers::mat4_decompose};
 
-#[derive(NodeComponent, Clone)]
+#[derive(NodeComponent, Clone, Serialize, Deserialize, Reflect)]
+#[component(serialize, reflect)]
 pub struct Camera {
+	#[serde(skip)]
 	owner: NodeId,
+	#[serde(skip)]
 	scene: Option<SharedSceneGraph>,
+	#[reflect(skip)]
 	camera: fatum_graphics::Camera,
 	active: bool
 }
@@ -14,7 +19,7 @@ pub struct Camera {
 impl Camera {
 	pub fn new(camera: fatum_graphics::Camera, active: bool) -> Self {
 		Self {
-			owner: Default::default(),
+			owner: NodeId::INVALID,
 			scene: Default::default(),
 			camera,
 			active
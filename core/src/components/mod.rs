@@ -11,4 +11,6 @@ mod camera;
 pub use camera::*;
 
 mod ui;
-pub use ui::*;
\ No newline at end of file
+pub use ui::*;
+mod registry;
+pub use registry::*;
@@ -6,6 +6,7 @@ use fatum_scene::{NodeComponent, NodeId, SceneGraph, SharedSceneGraph};
 use crate::components;
 
 #[derive(NodeComponent, Clone)]
+#[component(no_snapshot)]
 pub struct Model {
 	owner: NodeId,
 	scene: Option<SharedSceneGraph>,
@@ -15,7 +16,7 @@ pub struct Model {
 impl Model {
 	pub fn new(model: Rc<Box<fatum_graphics::Model>>) -> Self {
 		Self {
-			owner: 0,
+			owner: NodeId::INVALID,
 			scene: None,
 			model,
 		}
@@ -25,11 +26,9 @@ impl Model {
 	pub fn set_model(&mut self, model: Rc<Box<fatum_graphics::Model>>) {
 		self.model = model.clone();
 
-		if self.owner == 0 {
+		let Some(scene) = self.scene.clone() else {
 			return;
-		}
-
-		let scene = self.scene.clone().unwrap();
+		};
 
 		if let Ok(mut scene) = scene.write() {
 			let owner = scene.node_mut(self.owner).unwrap();
@@ -46,12 +45,12 @@ impl Model {
 
 impl Into<RenderObject> for Model {
 	fn into(self) -> RenderObject {
-		RenderObject::with_id(self.owner as u64, self.model.clone())
+		RenderObject::with_id(self.owner.to_bits(), self.model.clone())
 	}
 }
 
 impl Into<RenderObject> for &Model {
 	fn into(self) -> RenderObject {
-		RenderObject::with_id(self.owner as u64, self.model.clone())
+		RenderObject::with_id(self.owner.to_bits(), self.model.clone())
 	}
 }
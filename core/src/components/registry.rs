@@ -0,0 +1,52 @@
+use std::{path::PathBuf, sync::{Arc, Mutex}};
+
+use fatum_graphics::platform::GraphicsPlatform;
+use fatum_resources::{Resource, ResourcePlatform};
+use fatum_scene::{ComponentRegistry, error::{ErrorKind, SceneError}};
+use serde::{Deserialize, Serialize};
+
+use crate::{ResourceEngine, components::{Camera, Model, Sprite, Transform2D, Transform3D}, resources::ResTexture2D};
+
+/// Sprites are saved by the location of their texture in the assets directory.
+#[derive(Serialize, Deserialize)]
+struct SpriteData {
+	texture: PathBuf
+}
+
+/// Registers the built-in components that can be saved in scene files.
+pub fn register_components<P>(registry: &mut ComponentRegistry, resources: Arc<Mutex<ResourceEngine<P>>>)
+	where P: GraphicsPlatform + ResourcePlatform + Clone + 'static
+{
+	registry.register::<Transform2D>();
+	registry.register::<Transform3D>();
+	registry.register::<Camera>();
+
+	// recreated by the sprite it belongs to
+	registry.skip::<Model>();
+
+	let sprite_resources = resources.clone();
+	registry.register_with("Sprite",
+		move |sprite: &Sprite| {
+			let texture = sprite.texture();
+			let path = <ResTexture2D as Resource<P>>::path(&texture.borrow()).clone();
+
+			let mut resources = sprite_resources.lock().unwrap();
+			let texture = path.strip_prefix(resources.get().assets_directory())
+				.map_err(|_| SceneError::with_path(&path, ErrorKind::SerializationError, "Texture is not in the assets directory"))?
+				.to_path_buf();
+
+			Ok(SpriteData { texture })
+		},
+		move |data: SpriteData| {
+			let location = data.texture.to_string_lossy();
+
+			let texture = resources.lock().unwrap().get().load_by_path::<ResTexture2D>(&location, true)
+				.map_err(|e| SceneError::with_path(&data.texture, ErrorKind::Other, &format!("Failed to load texture: {}", e)))?;
+
+			let sprite = Sprite::new(texture);
+			let model = Model::new(sprite.model.clone());
+
+			Ok(vec![Box::new(sprite), Box::new(model)])
+		}
+	);
+}
@@ -3,7 +3,7 @@ use std::{cell::{LazyCell, OnceCell, RefCell}, rc::Rc, sync::{Arc, Mutex}};
 use fatum_graphics::{Color, Material, Mesh, Model, Vertex, render::RenderObject, texture::Texture2D};
 use fatum_macros::node_impl_new;
 use fatum_resources::ResourceRef;
-use fatum_scene::{Node, NodeComponent, NodeId, SceneGraph, SharedSceneGraph};
+use fatum_scene::{Node, NodeComponent, NodeId, Reflect, SceneGraph, SharedSceneGraph};
 use glam::{Vec2, Vec3};
 use static_init::dynamic;
 use crate::{components::{self, Transform2D}, resources::ResTexture2D};
@@ -27,11 +27,14 @@ static UNIT_QUAD: Model = Model {
 	]
 };
 
-#[derive(NodeComponent, Clone)]
+#[derive(NodeComponent, Clone, Reflect)]
+#[component(reflect)]
 pub struct Sprite {
 	owner: NodeId,
 	scene: Option<SharedSceneGraph>,
+	#[reflect(with = "crate::helpers::reflect::texture", read_only)]
 	texture: ResourceRef<ResTexture2D>,
+	#[reflect(skip)]
 	pub(crate) model: Rc<Box<fatum_graphics::Model>>
 }
 
@@ -43,7 +46,7 @@ impl Sprite {
 		let model = Rc::new(model);
 
 		Self {
-			owner: 0,
+			owner: NodeId::INVALID,
 			scene: None,
 			texture,
 			model
@@ -59,11 +62,9 @@ impl Sprite {
 
 		self.model = Rc::new(model);
 
-		if self.owner == 0 {
+		let Some(scene) = self.scene.clone() else {
 			return;
-		}
-
-		let scene = self.scene.clone().unwrap();
+		};
 
 		if let Ok(mut scene) = scene.write() {
 			let owner = scene.node_mut(self.owner).unwrap();
@@ -1,5 +1,7 @@
-use fatum_scene::{NodeComponent, NodeId, SceneGraph, SharedSceneGraph};
+use fatum_scene::{Node, NodeComponent, NodeId, Reflect, SceneGraph, SharedSceneGraph};
 use glam::{EulerRot, Mat4, Quat, Vec2, Vec3, Vec4};
+use serde::{Deserialize, Serialize};
+use rayon::prelude::*;
 use std::{fmt::Debug, sync::{Arc, Mutex}};
 
 use crate::helpers;
@@ -15,60 +17,87 @@ pub trait Transform {
 
 	fn dirty(&self) -> bool;
 	fn set_dirty(&mut self, dirty: bool);
+
+	/// Node of the transform the global matrix was last calculated under, `NodeId::INVALID` if there was none.
+	/// The matrix has to be calculated again once that changes, e.g. when the node is reparented.
+	fn parent_transform(&self) -> NodeId;
+	fn set_parent_transform(&mut self, parent: NodeId);
 }
 
-#[derive(Clone, NodeComponent)]
+// loaded transforms still need their matrices calculated
+fn dirty() -> bool { true }
+fn no_parent() -> NodeId { NodeId::INVALID }
+
+#[derive(Clone, NodeComponent, Serialize, Deserialize, Reflect)]
+#[component(serialize, reflect)]
 pub struct Transform3D {
+	#[serde(skip)]
 	owner: NodeId,
+	#[serde(skip)]
 	scene: Option<SharedSceneGraph>,
 
+	#[serde(skip)]
+	#[reflect(skip)]
 	pub(crate) local_matrix: Mat4,
+	#[serde(skip)]
+	#[reflect(skip)]
 	pub(crate) global_matrix: Mat4,
 
+	#[reflect(set = "set_translation")]
 	translation: Vec3,
+	#[reflect(set = "set_rotation")]
 	rotation: Quat,
+	#[reflect(set = "set_scale")]
 	scale: Vec3,
 
-	pub(crate) dirty: bool
+	#[serde(skip, default = "dirty")]
+	#[reflect(skip)]
+	pub(crate) dirty: bool,
+	#[serde(skip, default = "no_parent")]
+	#[reflect(skip)]
+	pub(crate) parent_transform: NodeId
 }
 
 impl Transform3D {
 	pub fn new(translation: Vec3, rotation: Quat, scale: Vec3) -> Self {
 		Self {
-			owner: 0,
+			owner: NodeId::INVALID,
 			scene: None,
 			local_matrix: Mat4::IDENTITY,
 			global_matrix: Mat4::IDENTITY,
 			translation,
 			rotation,
 			scale,
-			dirty: true
+			dirty: true,
+			parent_transform: NodeId::INVALID
 		}
 	}
 
 	pub fn with_euler(translation: Vec3, order: EulerRot, rotation: Vec3, scale: Vec3) -> Self {
 		Self {
-			owner: 0,
+			owner: NodeId::INVALID,
 			scene: None,
 			local_matrix: Mat4::IDENTITY,
 			global_matrix: Mat4::IDENTITY,
 			translation,
 			rotation: Quat::from_euler(order, rotation.x, rotation.y, rotation.z),
 			scale,
-			dirty: true
+			dirty: true,
+			parent_transform: NodeId::INVALID
 		}
 	}
 
 	pub fn with_translation(translation: Vec3) -> Self {
 		Self {
-			owner: 0,
+			owner: NodeId::INVALID,
 			scene: None,
 			local_matrix: Mat4::IDENTITY,
 			global_matrix: Mat4::IDENTITY,
 			translation,
 			rotation: Quat::IDENTITY,
 			scale: Vec3::ONE,
-			dirty: true
+			dirty: true,
+			parent_transform: NodeId::INVALID
 		}
 	}
 
@@ -129,6 +158,16 @@ impl Transform3D {
 
 	pub fn scale(&self) -> Vec3 { self.scale }
 	pub fn set_scale(&mut self, scale: Vec3) { self.scale = scale; self.dirty = true; }
+
+	/// Sets translation, rotation and scale from a local matrix.
+	pub fn set_from_matrix(&mut self, matrix: Mat4) {
+		let (translation, rotation, scale) = helpers::mat4_decompose(matrix);
+
+		self.translation = translation;
+		self.rotation = rotation;
+		self.scale = scale;
+		self.dirty = true;
+	}
 }
 
 impl Transform for Transform3D {
@@ -148,19 +187,23 @@ impl Transform for Transform3D {
 
 	fn dirty(&self) -> bool { self.dirty }
 	fn set_dirty(&mut self, dirty: bool) { self.dirty = dirty }
+
+	fn parent_transform(&self) -> NodeId { self.parent_transform }
+	fn set_parent_transform(&mut self, parent: NodeId) { self.parent_transform = parent }
 }
 
 impl Default for Transform3D {
 	fn default() -> Self {
 		Self {
-			owner: 0,
+			owner: NodeId::INVALID,
 			scene: None,
 			local_matrix: Mat4::IDENTITY,
 			global_matrix: Mat4::IDENTITY,
 			translation: Vec3::ZERO,
 			rotation: Quat::IDENTITY,
 			scale: Vec3::ONE,
-			dirty: true
+			dirty: true,
+			parent_transform: NodeId::INVALID
 		}
 	}
 }
@@ -175,49 +218,67 @@ impl Debug for Transform3D {
 			.field("rotation", &self.rotation)
 			.field("scale", &self.scale)
 			.field("dirty", &self.dirty)
+			.field("parent_transform", &self.parent_transform)
 			.finish()
 	}
 }
 
-#[derive(Clone, NodeComponent)]
+#[derive(Clone, NodeComponent, Serialize, Deserialize, Reflect)]
+#[component(serialize, reflect)]
 pub struct Transform2D {
+	#[serde(skip)]
 	owner: NodeId,
+	#[serde(skip)]
 	scene: Option<SharedSceneGraph>,
 
+	#[serde(skip)]
+	#[reflect(skip)]
 	pub(crate) local_matrix: Mat4,
+	#[serde(skip)]
+	#[reflect(skip)]
 	pub(crate) global_matrix: Mat4,
 
+	#[reflect(set = "set_translation")]
 	translation: Vec2,
+	#[reflect(set = "set_rotation")]
 	rotation: f32,
+	#[reflect(set = "set_scale")]
 	scale: Vec2,
 
-	pub(crate) dirty: bool
+	#[serde(skip, default = "dirty")]
+	#[reflect(skip)]
+	pub(crate) dirty: bool,
+	#[serde(skip, default = "no_parent")]
+	#[reflect(skip)]
+	pub(crate) parent_transform: NodeId
 }
 
 impl Transform2D {
 	pub fn new(translation: Vec2, rotation: f32, scale: Vec2) -> Self {
 		Self {
-			owner: 0,
+			owner: NodeId::INVALID,
 			scene: None,
 			local_matrix: Mat4::IDENTITY,
 			global_matrix: Mat4::IDENTITY,
 			translation,
 			rotation,
 			scale,
-			dirty: true
+			dirty: true,
+			parent_transform: NodeId::INVALID
 		}
 	}
 
 	pub fn with_translation(translation: Vec2) -> Self {
 		Self {
-			owner: 0,
+			owner: NodeId::INVALID,
 			scene: None,
 			local_matrix: Mat4::IDENTITY,
 			global_matrix: Mat4::IDENTITY,
 			translation,
 			rotation: 0.0,
 			scale: Vec2::ONE,
-			dirty: true
+			dirty: true,
+			parent_transform: NodeId::INVALID
 		}
 	}
 
@@ -243,6 +304,16 @@ impl Transform2D {
 
 	pub fn scale(&self) -> Vec2 { self.scale }
 	pub fn set_scale(&mut self, scale: Vec2) { self.scale = scale; self.dirty = true; }
+
+	/// Sets translation, rotation and scale from a local matrix, anything outside of the XY plane is dropped.
+	pub fn set_from_matrix(&mut self, matrix: Mat4) {
+		let (translation, rotation, scale) = helpers::mat4_decompose(matrix);
+
+		self.translation = translation.truncate();
+		self.rotation = rotation.to_euler(EulerRot::XYZ).2;
+		self.scale = scale.truncate();
+		self.dirty = true;
+	}
 }
 
 impl Transform for Transform2D {
@@ -266,19 +337,23 @@ impl Transform for Transform2D {
 
 	fn dirty(&self) -> bool { self.dirty }
 	fn set_dirty(&mut self, dirty: bool) { self.dirty = dirty }
+
+	fn parent_transform(&self) -> NodeId { self.parent_transform }
+	fn set_parent_transform(&mut self, parent: NodeId) { self.parent_transform = parent }
 }
 
 impl Default for Transform2D {
 	fn default() -> Self {
 		Self {
-			owner: 0,
+			owner: NodeId::INVALID,
 			scene: None,
 			local_matrix: Mat4::IDENTITY,
 			global_matrix: Mat4::IDENTITY,
 			translation: Vec2::ZERO,
 			rotation: 0.0,
 			scale: Vec2::ONE,
-			dirty: true
+			dirty: true,
+			parent_transform: NodeId::INVALID
 		}
 	}
 }
@@ -293,6 +368,223 @@ impl Debug for Transform2D {
 			.field("rotation", &self.rotation)
 			.field("scale", &self.scale)
 			.field("dirty", &self.dirty)
+			.field("parent_transform", &self.parent_transform)
 			.finish()
 	}
 }
+
+/// Name of the system propagating transforms in `Stage::PostUpdate`. Systems moving nodes should run before it.
+pub const TRANSFORM_SYSTEM: &str = "transform";
+
+fn transform_of(node: &Node) -> Option<&dyn Transform> {
+	if let Some(t2d) = node.component::<Transform2D>() {
+		Some(t2d)
+	} else {
+		node.component::<Transform3D>().map(|t3d| t3d as &dyn Transform)
+	}
+}
+
+fn transform_of_mut(node: &mut Node) -> Option<&mut dyn Transform> {
+	if node.has_component::<Transform2D>() {
+		node.component_mut::<Transform2D>().map(|t2d| t2d as &mut dyn Transform)
+	} else {
+		node.component_mut::<Transform3D>().map(|t3d| t3d as &mut dyn Transform)
+	}
+}
+
+fn local_transform_matrix(node: &Node) -> Option<Mat4> {
+	transform_of(node).map(|transform| transform.calculate_matrix())
+}
+
+/// Subtrees with fewer transforms than this are propagated on the thread that got to them, below it their
+/// children are spread over the thread pool.
+const PARALLEL_SUBTREE: usize = 256;
+
+/// A transform copied out of the scene, so it can be propagated on other threads. Nodes and their components
+/// aren't `Sync`, this is.
+struct TransformEntry {
+	id: NodeId,
+	parent: Option<usize>,
+	children: Vec<usize>,
+
+	local_matrix: Mat4,
+	global_matrix: Mat4,
+	dirty: bool,
+
+	// transforms in the subtree, this one included, and whether any of them is dirty
+	size: usize,
+	dirty_below: bool
+}
+
+/// Recalculates the matrices of dirty transforms, of transforms that were moved under another one, and of the ones
+/// below them, in parallel across subtrees. Returns the nodes whose global matrix changed along with it, in depth-first order.
+///
+/// Transforms are copied out of the scene first, then propagated on the rayon thread pool, then the changed
+/// matrices are written back. Copying and writing back stay on the calling thread, nodes and their components
+/// aren't `Send`. Transforms below a node without one start from the identity.
+pub fn propagate_transforms(scene: &mut SceneGraph) -> Vec<(NodeId, Mat4)> {
+	let (entries, roots) = snapshot_transforms(scene);
+
+	let mut changed = propagate_entries(&entries, &roots, Mat4::IDENTITY, false);
+	// roots below nodes without a transform come after the subtree they're in
+	changed.sort_unstable_by_key(|(index, _)| *index);
+
+	changed.into_iter()
+		.map(|(index, global_matrix)| {
+			let entry = &entries[index];
+			let t = transform_of_mut(scene.node_mut(entry.id).unwrap()).unwrap();
+
+			t.set_local_matrix(entry.local_matrix);
+			t.set_global_matrix(global_matrix);
+			t.set_dirty(false);
+			t.set_parent_transform(entry.parent.map_or(NodeId::INVALID, |parent| entries[parent].id));
+
+			(entry.id, global_matrix)
+		})
+		.collect()
+}
+
+/// The scene's transforms in depth-first order, and the ones whose parent has no transform.
+fn snapshot_transforms(scene: &SceneGraph) -> (Vec<TransformEntry>, Vec<usize>) {
+	let mut entries: Vec<TransformEntry> = Vec::new();
+	let mut roots = Vec::new();
+
+	// nodes along with the entry of their parent's transform
+	let mut stack: Vec<(NodeId, Option<usize>)> = scene.children_slice(scene.root_id()).iter().rev()
+		.map(|child| (*child, None))
+		.collect();
+
+	while let Some((id, parent)) = stack.pop() {
+		let node = scene.node(id).unwrap();
+		let mut below = None;
+
+		if let Some(t) = transform_of(node) {
+			let index = entries.len();
+			// moved to under another transform since the global matrix was calculated
+			let dirty = t.dirty() || t.parent_transform() != parent.map_or(NodeId::INVALID, |parent| entries[parent].id);
+
+			match parent {
+				Some(parent) => entries[parent].children.push(index),
+				None => roots.push(index)
+			}
+
+			entries.push(TransformEntry {
+				id,
+				parent,
+				children: Vec::new(),
+				// otherwise the cached one is still right
+				local_matrix: if t.dirty() { t.calculate_matrix() } else { t.local_matrix() },
+				global_matrix: t.global_matrix(),
+				dirty,
+				size: 1,
+				dirty_below: dirty
+			});
+
+			below = Some(index);
+		}
+
+		for child in scene.children_slice(id).iter().rev() {
+			stack.push((*child, below));
+		}
+	}
+
+	// children come after their parents
+	for index in (0..entries.len()).rev() {
+		if let Some(parent) = entries[index].parent {
+			let (size, dirty_below) = (entries[index].size, entries[index].dirty_below);
+			entries[parent].size += size;
+			entries[parent].dirty_below |= dirty_below;
+		}
+	}
+
+	(entries, roots)
+}
+
+fn propagate_entries(entries: &[TransformEntry], indices: &[usize], parent_matrix: Mat4, parent_changed: bool) -> Vec<(usize, Mat4)> {
+	let size: usize = indices.iter().map(|index| entries[*index].size).sum();
+
+	if size < PARALLEL_SUBTREE {
+		let mut changed = Vec::new();
+
+		for index in indices {
+			propagate_entry(entries, *index, parent_matrix, parent_changed, &mut changed);
+		}
+
+		return changed;
+	}
+
+	indices.par_iter()
+		.fold(Vec::new, |mut changed, index| {
+			propagate_entry(entries, *index, parent_matrix, parent_changed, &mut changed);
+			changed
+		})
+		.reduce(Vec::new, |mut changed, other| {
+			changed.extend(other);
+			changed
+		})
+}
+
+fn propagate_entry(entries: &[TransformEntry], index: usize, parent_matrix: Mat4, parent_changed: bool, changed: &mut Vec<(usize, Mat4)>) {
+	let entry = &entries[index];
+
+	if !parent_changed && !entry.dirty_below {
+		return;
+	}
+
+	// node is dirty if it itself is dirty OR its parent is dirty
+	let is_changed = parent_changed || entry.dirty;
+	let global_matrix = if is_changed { parent_matrix * entry.local_matrix } else { entry.global_matrix };
+
+	if is_changed {
+		changed.push((index, global_matrix));
+	}
+
+	if entry.size < PARALLEL_SUBTREE {
+		for child in &entry.children {
+			propagate_entry(entries, *child, global_matrix, is_changed, changed);
+		}
+	} else {
+		changed.extend(propagate_entries(entries, &entry.children, global_matrix, is_changed));
+	}
+}
+
+/// Global matrix of the node, calculated from its transform and the ones of its parents. Unlike
+/// `Transform::global_matrix` this is up to date before the scene is processed.
+pub fn calculate_global_matrix(scene: &SceneGraph, node: NodeId) -> Mat4 {
+	let mut matrix = Mat4::IDENTITY;
+	let mut current = node;
+
+	while let Some(node) = scene.node(current) {
+		if let Some(local_matrix) = local_transform_matrix(node) {
+			matrix = local_matrix * matrix;
+		}
+
+		if current == scene.root_id() {
+			break;
+		}
+
+		current = scene.parent(current);
+	}
+
+	matrix
+}
+
+/// `SceneGraph::reparent`, but the node's `Transform2D`/`Transform3D` is changed so it stays where it was in the world.
+pub fn reparent_keep_global(scene: &mut SceneGraph, node: NodeId, new_parent: NodeId) -> bool {
+	let global_matrix = calculate_global_matrix(scene, node);
+
+	if !scene.reparent(node, new_parent) {
+		return false;
+	}
+
+	let local_matrix = calculate_global_matrix(scene, new_parent).inverse() * global_matrix;
+	let node = scene.node_mut(node).unwrap();
+
+	if let Some(t2d) = node.component_mut::<Transform2D>() {
+		t2d.set_from_matrix(local_matrix);
+	} else if let Some(t3d) = node.component_mut::<Transform3D>() {
+		t3d.set_from_matrix(local_matrix);
+	}
+
+	true
+}
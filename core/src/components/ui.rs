@@ -1,6 +1,7 @@
 use fatum_scene::{NodeComponent, NodeId, SharedSceneGraph};
 
 #[derive(NodeComponent)]
+#[component(no_clone, no_snapshot)]
 pub struct UiElement {
 	owner: NodeId,
 	scene: Option<SharedSceneGraph>,
@@ -10,7 +11,7 @@ pub struct UiElement {
 impl UiElement {
 	pub fn new<F: Fn(std::time::Duration, &Self, &egui::Context) -> () + 'static>(draw_function: F) -> Self {
 		Self {
-			owner: Default::default(),
+			owner: NodeId::INVALID,
 			scene: Default::default(),
 			draw_function: Box::new(draw_function)
 		}
@@ -20,9 +21,3 @@ impl UiElement {
 		(self.draw_function)(delta, self, ui);
 	}
 }
-
-impl Clone for UiElement {
-	fn clone(&self) -> Self {
-		panic!("Unsupported");
-	}
-}
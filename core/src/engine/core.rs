@@ -5,6 +5,8 @@ use fatum_graphics::RenderWindow;
 use fatum_graphics::platform::opengl::OpenGlWindow;
 use fatum_graphics::{platform::{GraphicsPlatform, opengl::OpenGlPlatform}, render::{PipelineKind, RenderTarget}};
 use fatum_resources::{ResourcePlatform, Resources};
+use fatum_scene::{ComponentRegistry, PrefabLibrary, System};
+use fatum_signals::{Executor, SignalQueue};
 use serde::{Deserialize, Serialize};
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
@@ -14,7 +16,7 @@ use winit::event_loop::EventLoop;
 use winit::platform::run_on_demand::EventLoopExtRunOnDemand;
 use winit::platform::x11::EventLoopBuilderExtX11;
 
-use crate::{Application, ApplicationInfo, GraphicsEngine, InputEngine, ResourceEngine, SceneEngine, UiEngine};
+use crate::{components, Application, ApplicationInfo, GraphicsEngine, InputEngine, ResourceEngine, SceneEngine, UiEngine};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OutputKind {
@@ -31,6 +33,7 @@ pub struct CoreEngine<P: GraphicsPlatform + ResourcePlatform, A: Application<P>>
 	scene: Rc<RefCell<SceneEngine<P>>>,
 	input: Rc<RefCell<InputEngine<P>>>,
 	ui: Option<Rc<RefCell<UiEngine<P>>>>,
+	prefabs: Option<PrefabLibrary>,
 
 	pub running: bool,
 
@@ -70,6 +73,9 @@ impl<P, A> CoreEngine<P, A> where P: GraphicsPlatform + ResourcePlatform + Clone
 				.init().unwrap();
 		}
 
+		// deferred emissions are flushed below, after processing the scene
+		SignalQueue::set_flushing_thread();
+
 		let base_directory = std::env::current_exe().map_or(
 			Path::new(file!()).parent().unwrap().join(env!("CARGO_MANIFEST_DIR")),
 			|p| {
@@ -99,6 +105,7 @@ impl<P, A> CoreEngine<P, A> where P: GraphicsPlatform + ResourcePlatform + Clone
 			scene,
 			input,
 			ui: None,
+			prefabs: None,
 			running: false,
 			last_loop: time::Instant::now(),
 			loop_delta: time::Duration::from_secs(0)
@@ -111,6 +118,38 @@ impl<P, A> CoreEngine<P, A> where P: GraphicsPlatform + ResourcePlatform + Clone
 	pub fn input_engine(&mut self) -> RefMut<InputEngine<P>> { self.input.borrow_mut() }
 	pub fn ui_engine(&mut self) -> RefMut<UiEngine<P>> { self.ui.as_mut().unwrap().borrow_mut() }
 
+	/// A registry with the built-in components, for saving and loading scenes. Sprites load their textures
+	/// through this engine's resources.
+	pub fn component_registry(&self) -> ComponentRegistry where P: 'static {
+		let mut registry = ComponentRegistry::new();
+		components::register_components(&mut registry, self.resources.clone());
+		registry
+	}
+
+	/// Prefabs in the assets directory, loaded with `component_registry`.
+	pub fn prefabs(&mut self) -> &mut PrefabLibrary where P: 'static {
+		if self.prefabs.is_none() {
+			let directory = self.resource_engine().get().assets_directory().clone();
+			self.prefabs = Some(PrefabLibrary::new(directory, self.component_registry()));
+		}
+
+		self.prefabs.as_mut().unwrap()
+	}
+
+	/// Adds a system run on every scene each frame, e.g. before or after `components::TRANSFORM_SYSTEM`.
+	/// See `Schedule::add`.
+	pub fn add_system(&mut self, system: System) -> bool {
+		self.scene_engine().add_system(system)
+	}
+
+	pub fn remove_system(&mut self, name: &str) -> bool {
+		self.scene_engine().remove_system(name)
+	}
+
+	pub fn is_paused(&mut self) -> bool { self.scene_engine().is_paused() }
+	/// See `SceneEngine::set_paused`.
+	pub fn set_paused(&mut self, paused: bool) { self.scene_engine().set_paused(paused); }
+
 	// pub fn graphics(&mut self) -> &mut P { self.graphics_engine().get() }
 	// pub fn resources(&mut self) -> &mut Resources<P> { self.resource_engine().get() }
 
@@ -195,6 +234,10 @@ impl<P, A> ApplicationHandler<()> for CoreEngine<P, A> where P: GraphicsPlatform
 
 				self.scene_engine().process(delta);
 
+				// deferred signals go out here, once nothing holds a scene lock anymore
+				SignalQueue::flush();
+				Executor::update(delta);
+
 				let active = self.graphics_engine().begin(window_id);
 
 				if active {
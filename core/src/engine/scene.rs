@@ -1,239 +1,557 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc, sync::{Arc, Mutex, RwLockWriteGuard}};
+use std::{cell::RefCell, collections::HashMap, rc::Rc, sync::{Arc, Mutex, RwLockWriteGuard, atomic::{AtomicU64, Ordering}}, time::Duration};
 
-use fatum_graphics::{Camera, platform::GraphicsPlatform, render::{RenderObject, RenderQueue}};
+use fatum_graphics::{Camera, Color, platform::GraphicsPlatform, render::{RenderObject, RenderQueue}};
 use fatum_resources::ResourcePlatform;
-use fatum_scene::{Node, NodeId, SceneGraph, SharedSceneGraph, iterators::{SceneDfsIterator, ScenePostDfsIterator}};
-use fatum_signals::SignalDispatcher;
+use fatum_scene::{Node, NodeId, SceneGraph, Schedule, SharedSceneGraph, Stage, System, iterators::ScenePostDfsIterator};
+use fatum_signals::{Connection, SignalDispatcher, StaticSignal};
 use glam::{Mat4, Quat, Vec3, Vec4};
 use signals2::Connect2;
 
-use crate::{Application, CoreEngine, GraphicsEngine, components::{self, Model, Transform, Transform2D, Transform3D}};
+use crate::{Application, CoreEngine, GraphicsEngine, components::{self, Model}};
+
+enum QueueChange {
+	Add(NodeId, RenderObject),
+	Remove(NodeId)
+}
+
+/// Render queue changes made by scene signal handlers, applied to the queue in `process`.
+/// Handlers only hold it weakly, so they stop doing anything once their scene is unloaded.
+type PendingChanges = Rc<RefCell<Vec<QueueChange>>>;
+
+static NEXT_SCENE_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Identifies a scene loaded into a `SceneEngine`, even if the same scene is loaded again later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SceneId(u64);
+
+/// A fade to a color and back, see `SceneEngine::change_scene`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transition {
+	pub color: Color,
+	pub fade_out: Duration,
+	pub fade_in: Duration
+}
+
+impl Transition {
+	/// Through black, half of the duration each way.
+	pub fn fade(duration: Duration) -> Self {
+		Self {
+			color: Color::from_rgb_f32(0.0, 0.0, 0.0),
+			fade_out: duration / 2,
+			fade_in: duration / 2
+		}
+	}
+}
+
+struct ActiveTransition {
+	transition: Transition,
+	elapsed: Duration,
+	// taken once the screen is covered
+	next: Option<SharedSceneGraph>
+}
+
+impl ActiveTransition {
+	/// How much of the screen is covered, from 0 to 1.
+	fn coverage(&self) -> f32 {
+		let Transition { fade_out, fade_in, .. } = self.transition;
+
+		if self.elapsed < fade_out {
+			ratio(self.elapsed, fade_out)
+		} else {
+			1.0 - ratio(self.elapsed - fade_out, fade_in)
+		}
+	}
+
+	fn is_finished(&self) -> bool {
+		self.next.is_none() && self.elapsed >= self.transition.fade_out + self.transition.fade_in
+	}
+}
+
+fn ratio(elapsed: Duration, total: Duration) -> f32 {
+	if total.is_zero() {
+		return 1.0;
+	}
+
+	(elapsed.as_secs_f32() / total.as_secs_f32()).min(1.0)
+}
+
+/// A scene loaded for an output, along with the render objects it put into the output's queue.
+struct LoadedScene {
+	id: SceneId,
+	scene: SharedSceneGraph,
+	additive: bool,
+	// whether its objects are drawn, scenes covered by a pushed one aren't
+	visible: bool,
+
+	pending: PendingChanges,
+	connections: Vec<Connection>,
+	objects: HashMap<NodeId, RenderObject>
+}
+
+impl LoadedScene {
+	fn load(scene: SharedSceneGraph, additive: bool, queue: &mut Box<dyn RenderQueue>) -> Self {
+		let mut loaded = Self {
+			id: SceneId(NEXT_SCENE_ID.fetch_add(1, Ordering::Relaxed)),
+			scene: scene.clone(),
+			additive,
+			visible: true,
+			pending: Rc::default(),
+			connections: Vec::new(),
+			objects: HashMap::new()
+		};
+
+		let nodes: Vec<NodeId> = ScenePostDfsIterator::new(scene.clone(), Default::default())
+			.collect();
+
+		let mut scene = scene.write().unwrap();
+
+		for node in nodes {
+			let node = scene.node_mut(node)
+				.expect("An invalid node was provided by the post-order DFS traverse iterator?");
+
+			if let Some(model) = node.component::<Model>() {
+				loaded.add_object(queue, node.id(), RenderObject::new(model.model()));
+			}
+
+			loaded.connections.push(node.component_added.connect_weak(Rc::downgrade(&loaded.pending), |pending, args| {
+				let node = unsafe { &*args.0 };
+				let component = unsafe { &*args.1 };
+
+				if let Some(model) = component.as_any().downcast_ref::<Model>() {
+					pending.borrow_mut().push(QueueChange::Add(node.id(), RenderObject::new(model.model())));
+				}
+			}));
+
+			loaded.connections.push(node.component_removed.connect_weak(Rc::downgrade(&loaded.pending), |pending, args| {
+				let node = unsafe { &*args.0 };
+				let component = unsafe { &*args.1 };
+
+				if component.as_any().is::<Model>() {
+					pending.borrow_mut().push(QueueChange::Remove(node.id()));
+				}
+			}));
+
+			node.ready();
+		}
+
+		loaded.connections.push(scene.node_added.connect_weak(Rc::downgrade(&loaded.pending), |pending, args| {
+			let node = unsafe { &*args.1 };
+
+			if let Some(model) = node.component::<Model>() {
+				pending.borrow_mut().push(QueueChange::Add(node.id(), RenderObject::new(model.model())));
+			}
+
+			node.ready();
+		}));
+
+		loaded.connections.push(scene.node_removed.connect_weak(Rc::downgrade(&loaded.pending), |pending, args| {
+			let node = unsafe { &*args.1 };
+
+			if node.has_component::<Model>() {
+				pending.borrow_mut().push(QueueChange::Remove(node.id()));
+			}
+		}));
+
+		loaded
+	}
+
+	/// Disconnects its handlers and takes its objects out of the queue.
+	fn unload(self, queue: Option<&mut Box<dyn RenderQueue>>) -> SharedSceneGraph {
+		for connection in &self.connections {
+			connection.disconnect();
+		}
+
+		if let Some(queue) = queue {
+			for object in self.objects.values() {
+				queue.remove_object(object);
+			}
+		}
+
+		self.scene
+	}
+
+	// scenes can share node ids, so objects get their own instead of the node's
+	fn add_object(&mut self, queue: &mut Box<dyn RenderQueue>, node: NodeId, object: RenderObject) {
+		queue.add_object(&object, Mat4::IDENTITY);
+
+		if !self.visible {
+			queue.set_object_visible(&object, false);
+		}
+
+		if let Some(previous) = self.objects.insert(node, object) {
+			queue.remove_object(&previous);
+		}
+	}
+
+	fn apply_changes(&mut self, queue: &mut Box<dyn RenderQueue>) {
+		let changes: Vec<QueueChange> = self.pending.borrow_mut().drain(..).collect();
+
+		for change in changes {
+			match change {
+				QueueChange::Add(node, object) => self.add_object(queue, node, object),
+				QueueChange::Remove(node) => {
+					if let Some(object) = self.objects.remove(&node) {
+						queue.remove_object(&object);
+					}
+				}
+			}
+		}
+	}
+
+	fn hide(&mut self, queue: &mut Box<dyn RenderQueue>) {
+		if !self.visible {
+			return;
+		}
+
+		for object in self.objects.values() {
+			queue.set_object_visible(object, false);
+		}
+
+		self.visible = false;
+	}
+}
+
+/// The scenes loaded for one output, bottom first.
+#[derive(Default)]
+struct SceneStack {
+	scenes: Vec<LoadedScene>,
+	transition: Option<ActiveTransition>
+}
+
+impl SceneStack {
+	/// Where the scenes that process and draw start: the topmost pushed scene, with the scenes added over it.
+	fn first_active(&self) -> usize {
+		self.scenes.iter().rposition(|loaded| !loaded.additive).unwrap_or(0)
+	}
+}
 
 pub struct SceneEngine<P: GraphicsPlatform> {
 	graphics: Rc<RefCell<GraphicsEngine<P>>>,
-	scenes: HashMap<usize, SharedSceneGraph>,
+	outputs: HashMap<usize, SceneStack>,
+
+	schedule: Schedule,
+	paused: bool,
+	// global matrices changed by the transform system, applied to the render queue after the schedule ran
+	moved: Rc<RefCell<Vec<(NodeId, Mat4)>>>,
+
+	/// Emitted with the output and the scene once a scene is unloaded: its objects are out of the render queue and
+	/// the engine doesn't handle its signals anymore.
+	pub scene_unloaded: StaticSignal<(usize, SceneId)>
 }
 
 impl<P> SceneEngine<P> where P: GraphicsPlatform {
 	pub fn new(graphics: Rc<RefCell<GraphicsEngine<P>>>) -> Self {
 		log::info!("Created scene engine");
 
+		let moved: Rc<RefCell<Vec<(NodeId, Mat4)>>> = Rc::default();
+		let mut schedule = Schedule::new();
+
+		let transforms = moved.clone();
+		schedule.add(System::new(components::TRANSFORM_SYSTEM, Stage::PostUpdate, move |scene, _| {
+			transforms.borrow_mut().extend(components::propagate_transforms(scene));
+		}));
+
 		Self {
 			graphics,
-			scenes: HashMap::new()
+			outputs: HashMap::new(),
+			schedule,
+			paused: false,
+			moved,
+			scene_unloaded: StaticSignal::new()
 		}
 	}
 
-	pub fn scenes(&self) -> &HashMap<usize, SharedSceneGraph> {
-		&self.scenes
+	/// The topmost scene of the output.
+	pub fn scene(&self, queue_index: usize) -> Option<SharedSceneGraph> {
+		self.outputs.get(&queue_index)?.scenes.last().map(|loaded| loaded.scene.clone())
 	}
 
-	pub fn scene(&self, queue_index: usize) -> Option<SharedSceneGraph> {
-		self.scenes.get(&queue_index).map_or(None, |v| Some(v.clone()))
+	/// Every scene loaded for the output, bottom first.
+	pub fn scenes(&self, queue_index: usize) -> Vec<SharedSceneGraph> {
+		self.outputs.get(&queue_index).map_or(Vec::new(), |stack| {
+			stack.scenes.iter().map(|loaded| loaded.scene.clone()).collect()
+		})
+	}
+
+	/// The scenes of the output that process and draw, bottom first. Scenes under a pushed scene wait for it to be
+	/// popped.
+	pub fn active_scenes(&self, queue_index: usize) -> Vec<SharedSceneGraph> {
+		self.outputs.get(&queue_index).map_or(Vec::new(), |stack| {
+			stack.scenes[stack.first_active()..].iter().map(|loaded| loaded.scene.clone()).collect()
+		})
+	}
+
+	pub fn scene_id(&self, scene: &SharedSceneGraph) -> Option<SceneId> {
+		self.outputs.values()
+			.flat_map(|stack| &stack.scenes)
+			.find(|loaded| Arc::ptr_eq(&loaded.scene, scene))
+			.map(|loaded| loaded.id)
 	}
 
+	/// Unloads every scene of the output and loads this one instead. Cancels a running transition.
 	pub fn set_scene(&mut self, queue_index: usize, scene: SharedSceneGraph) -> Option<bool> {
 		log::info!("Setting scene for output {}: {:?}", queue_index, scene);
 
-		let mut graphics = self.graphics.borrow_mut();
+		self.graphics.borrow_mut().queue(queue_index)?;
 
-		let queue = graphics.queue(queue_index)?;
+		if let Some(stack) = self.outputs.get_mut(&queue_index) {
+			stack.transition = None;
+		}
 
-		{
-			let nodes: Vec<u32> = ScenePostDfsIterator::new(scene.clone(), Default::default())
-				.collect();
+		self.replace_scenes(queue_index, scene);
+		Some(true)
+	}
 
-			let mut scene = scene.write().unwrap();
+	/// Loads the scene over the ones already loaded for the output, which stop processing and drawing until it's
+	/// popped, e.g. a menu over a level.
+	pub fn push_scene(&mut self, queue_index: usize, scene: SharedSceneGraph) -> Option<SceneId> {
+		self.load_scene(queue_index, scene, false, None)
+	}
 
-			for node in nodes {
-				let node = scene.node_mut(node)
-					.expect("An invalid node was provided by the post-order DFS traverse iterator?");
+	/// Loads the scene over the ones already loaded for the output, which keep running, e.g. a HUD over a level.
+	pub fn add_scene(&mut self, queue_index: usize, scene: SharedSceneGraph) -> Option<SceneId> {
+		self.load_scene(queue_index, scene, true, None)
+	}
 
-				if let Some(model) = node.component::<Model>() {
-					let render_object: RenderObject = model.into();
-					queue.add_object(&render_object, Mat4::IDENTITY);
-				}
+	/// Unloads the topmost scene of the output.
+	pub fn pop_scene(&mut self, queue_index: usize) -> Option<SharedSceneGraph> {
+		let id = self.outputs.get(&queue_index)?.scenes.last()?.id;
+		self.remove_scene(id)
+	}
 
-				// :3
-				node.component_added.connect_capture(vec![queue as *mut _ as *mut std::ffi::c_void], |captures, args| {
-					unsafe {
-						let queue = &mut *(captures[0] as *mut Box<dyn RenderQueue>);
-						let component = &*args.1;
+	/// Unloads the scene wherever it is in its output's stack.
+	pub fn remove_scene(&mut self, id: SceneId) -> Option<SharedSceneGraph> {
+		let (queue_index, stack) = self.outputs.iter_mut()
+			.find(|(_, stack)| stack.scenes.iter().any(|loaded| loaded.id == id))?;
+		let queue_index = *queue_index;
 
-						if let Some(model) = component.as_any().downcast_ref::<Model>() {
-							let render_object: RenderObject = model.into();
-							queue.add_object(&render_object, Mat4::IDENTITY);
-						}
-					}
-				});
+		let index = stack.scenes.iter().position(|loaded| loaded.id == id).unwrap();
+		let loaded = stack.scenes.remove(index);
 
-				node.component_removed.connect_capture(vec![queue as *mut _ as *mut std::ffi::c_void], |captures, args| {
-					unsafe {
-						let queue = &mut *(captures[0] as *mut Box<dyn RenderQueue>);
-						let component = &*args.1;
+		let mut graphics = self.graphics.borrow_mut();
+		let scene = loaded.unload(graphics.queue(queue_index));
+		drop(graphics);
 
-						if let Some(model) = component.as_any().downcast_ref::<Model>() {
-							let render_object: RenderObject = model.into();
-							queue.remove_object(&render_object);
-						}
-					}
-				});
+		log::info!("Scene unloaded from output {}", queue_index);
+		self.scene_unloaded.emit((queue_index, id));
+		Some(scene)
+	}
 
-				node.ready();
-			}
+	/// Fades out, replaces the topmost pushed scene of the output with this one once the screen is covered, and fades
+	/// back in. Scenes added over the replaced one stay, e.g. a HUD over a level, and the old scene keeps running while
+	/// fading out. Changing again during a transition picks up from how much of the screen is already covered.
+	/// Returns `false` if the output doesn't exist.
+	pub fn change_scene(&mut self, queue_index: usize, scene: SharedSceneGraph, transition: Transition) -> bool {
+		if self.graphics.borrow_mut().queue(queue_index).is_none() {
+			return false;
 		}
 
-		{
-			let mut scene = scene.write().unwrap();
+		let stack = self.outputs.entry(queue_index).or_default();
+		let elapsed = stack.transition.as_ref()
+			.map_or(Duration::ZERO, |current| transition.fade_out.mul_f32(current.coverage()));
 
-			scene.node_added.connect_capture(vec![queue as *mut _ as *mut std::ffi::c_void], |captures, args| {
-				unsafe {
-					let queue = &mut *(captures[0] as *mut Box<dyn RenderQueue>);
-					let node = &*args.1;
+		stack.transition = Some(ActiveTransition { transition, elapsed, next: Some(scene) });
+		true
+	}
 
-					if let Some(model) = node.component::<Model>() {
-						let render_object: RenderObject = model.into();
-						queue.add_object(&render_object, Mat4::IDENTITY);
-					}
+	pub fn is_transitioning(&self, queue_index: usize) -> bool {
+		self.outputs.get(&queue_index).is_some_and(|stack| stack.transition.is_some())
+	}
 
-					node.ready();
-				}
-			});
+	/// The color covering the output during a transition, its alpha following the fade.
+	pub fn fade(&self, queue_index: usize) -> Option<Color> {
+		let transition = self.outputs.get(&queue_index)?.transition.as_ref()?;
+		let color = transition.transition.color;
 
-			scene.node_removed.connect_capture(vec![queue as *mut _ as *mut std::ffi::c_void], |captures, args| {
-				unsafe {
-					let queue = &mut *(captures[0] as *mut Box<dyn RenderQueue>);
-					let node = &*args.1;
+		Some(Color::from_rgba_f32(color.r, color.g, color.b, color.a * transition.coverage()))
+	}
 
-					if let Some(model) = node.component::<Model>() {
-						let render_object: RenderObject = model.into();
-						queue.remove_object(&render_object);
-					}
-				}
-			});
+	/// Loads the scene at `index` in the output's stack, or on top.
+	fn load_scene(&mut self, queue_index: usize, scene: SharedSceneGraph, additive: bool, index: Option<usize>) -> Option<SceneId> {
+		if let Some(id) = self.scene_id(&scene) {
+			log::warn!("Scene {:?} is already loaded, not loading it again", id);
+			return None;
 		}
 
-		self.scenes.insert(queue_index, scene);
+		let mut graphics = self.graphics.borrow_mut();
+		let queue = graphics.queue(queue_index)?;
+
+		let loaded = LoadedScene::load(scene, additive, queue);
+		let id = loaded.id;
+
+		let scenes = &mut self.outputs.entry(queue_index).or_default().scenes;
+		scenes.insert(index.unwrap_or(scenes.len()).min(scenes.len()), loaded);
 		log::info!("Scene imported for output {}", queue_index);
-		Some(true)
+		Some(id)
 	}
 
-	pub fn process(&mut self, delta: std::time::Duration) -> bool {
-		for (output, scene) in &self.scenes {
-			if let Some(queue) = self.graphics.borrow_mut().queue(*output) {
-				let nodes: Vec<u32> = SceneDfsIterator::new(scene.clone(), Default::default())
-					.collect();
-
-				let mut camera_data: Option<fatum_graphics::Camera> = None;
-				let mut matrix_delta: HashMap<NodeId, (Mat4, Mat4)> = HashMap::new();
-
-				if let Ok(scene) = scene.try_read() {
-					for node in &nodes {
-						let node = scene.node(*node)
-							.expect("Iterator returned a non-existing node");
-
-						if node.id() == Default::default() {
-							continue; // ignore root
-						}
-
-						node.emit("update", delta);
-
-						if camera_data.is_none() && let Some(c) = node.component::<components::Camera>() {
-							if c.is_active() {
-								camera_data = Some(c.into());
-							}
-						}
-
-						let parent = node.parent();
-
-						let (parent_dirty, parent_global_matrix) =
-							if let Some((_, global_matrix)) = matrix_delta.get(&parent) {
-								(true, *global_matrix)
-							} else if let Some(parent) = scene.node(parent) {
-								if let Some(parent_t2d) = parent.component::<Transform2D>() {
-									(parent_t2d.dirty, parent_t2d.global_matrix)
-								} else if let Some(parent_t3d) = parent.component::<Transform3D>() {
-									(parent_t3d.dirty, parent_t3d.global_matrix)
-								} else {
-									(false, Mat4::IDENTITY)
-								}
-							} else {
-								(false, Mat4::IDENTITY)
-							};
-
-						if let Some(t2d) = node.component::<Transform2D>() {
-							// node is dirty if it itself is dirty OR its parent is dirty
-							let dirty = parent_dirty | t2d.dirty;
-
-							if !dirty {
-								continue;
-							}
-
-							let local_matrix = t2d.calculate_matrix();
-							let global_matrix = parent_global_matrix * local_matrix;
-
-							matrix_delta.insert(node.id(), (local_matrix, global_matrix));
-						} else if let Some(t3d) = node.component::<Transform3D>() {
-							let dirty = parent_dirty | t3d.dirty;
-
-							if !dirty {
-								continue;
-							}
-
-							let local_matrix = t3d.calculate_matrix();
-							let global_matrix = parent_global_matrix * local_matrix;
-
-							matrix_delta.insert(node.id(), (local_matrix, global_matrix));
-						}
-					}
-				} else {
-					log::warn!("Cannot process scene: could not get a read lock");
-					continue;
-				}
+	fn replace_scenes(&mut self, queue_index: usize, scene: SharedSceneGraph) {
+		let unloaded: Vec<SceneId> = self.outputs.get_mut(&queue_index)
+			.map_or(Vec::new(), |stack| stack.scenes.iter().rev().map(|loaded| loaded.id).collect());
 
-				if let Ok(mut scene) = scene.try_write() {
-					for node in &nodes {
-						let node = scene.node_mut(*node).unwrap();
-						node.emit_mut("$update", delta);
-
-						if !matrix_delta.contains_key(&node.id()) {
-							continue; // didn't change
-						}
-
-						let (local_matrix, global_matrix) = matrix_delta.get(&node.id()).unwrap();
-						let t: &mut dyn Transform;
-
-						if let Some(t2d) = node.component_mut::<Transform2D>() {
-							t = t2d;
-						} else if let Some(t3d) = node.component_mut::<Transform3D>() {
-							t = t3d;
-						} else {
-							log::warn!("{:?} had its transform processed in the read pass, but now it doesn't have one?", node);
-							continue;
-						}
-						
-						t.set_local_matrix(*local_matrix);
-						t.set_global_matrix(*global_matrix);
-						t.set_dirty(false);
-
-						if let Some(model) = node.component::<Model>() {
-							let render_object: RenderObject = model.into();
-							queue.set_object_matrix(&render_object, *global_matrix);
-						}
-					}
-				} else {
-					log::warn!("Cannot process scene: could not get a write lock");
-				}
+		for id in unloaded {
+			self.remove_scene(id);
+		}
+
+		self.load_scene(queue_index, scene, false, None);
+	}
+
+	fn swap_scene(&mut self, queue_index: usize, scene: SharedSceneGraph) {
+		let replaced = self.outputs.get(&queue_index).and_then(|stack| {
+			let index = stack.scenes.iter().rposition(|loaded| !loaded.additive)?;
+			Some((index, stack.scenes[index].id))
+		});
+
+		if let Some((_, id)) = replaced {
+			self.remove_scene(id);
+		}
+
+		// under everything if there were only added scenes
+		self.load_scene(queue_index, scene, false, Some(replaced.map_or(0, |(index, _)| index)));
+	}
+
+	pub fn schedule(&self) -> &Schedule { &self.schedule }
+
+	/// Adds a system run on every scene each frame. See `Schedule::add`.
+	pub fn add_system(&mut self, system: System) -> bool {
+		self.schedule.add(system)
+	}
+
+	pub fn remove_system(&mut self, name: &str) -> bool {
+		self.schedule.remove(name)
+	}
 
-				// set camera data
-				if let Some(camera_data) = camera_data {
-					queue.pipeline_mut().unwrap()
-						.camera_data().set_data(vec![camera_data].into());
-				} // TODO else set some default?
-			} else {
+	pub fn is_paused(&self) -> bool { self.paused }
+	/// Paused scenes only update nodes that process while paused, see `ProcessMode`. Systems keep running.
+	pub fn set_paused(&mut self, paused: bool) { self.paused = paused; }
+
+	/// Runs the systems in `Stage::PreUpdate`, then node updates, then the other stages, for each active scene of
+	/// each output. Nodes update by `SceneGraph::process_order`. Nodes queued for removal during the frame are removed
+	/// after the last stage. The camera comes from the topmost active scene that has one.
+	pub fn process(&mut self, delta: Duration) -> bool {
+		let outputs: Vec<usize> = self.outputs.keys().copied().collect();
+
+		for output in outputs {
+			self.advance_transition(output, delta);
+
+			let graphics = self.graphics.clone();
+			let mut graphics = graphics.borrow_mut();
+
+			let Some(queue) = graphics.queue(output) else {
 				log::warn!("Cannot process scene: could not get the render queue for output {}", output);
+				continue;
+			};
+
+			let Some(stack) = self.outputs.get_mut(&output) else { continue; };
+			let first_active = stack.first_active();
+			let mut scenes = std::mem::take(&mut stack.scenes);
+
+			for loaded in &mut scenes[..first_active] {
+				loaded.apply_changes(queue);
+				loaded.hide(queue);
 			}
+
+			let mut camera_data: Option<fatum_graphics::Camera> = None;
+
+			for loaded in &mut scenes[first_active..] {
+				camera_data = self.process_scene(loaded, queue, delta).or(camera_data);
+			}
+
+			self.outputs.get_mut(&output).unwrap().scenes = scenes;
+
+			// set camera data
+			if let Some(camera_data) = camera_data {
+				queue.pipeline_mut().unwrap()
+					.camera_data().set_data(vec![camera_data].into());
+			} // TODO else set some default?
 		}
+
 		true
 	}
+
+	fn advance_transition(&mut self, output: usize, delta: Duration) {
+		let Some(transition) = self.outputs.get_mut(&output).and_then(|stack| stack.transition.as_mut()) else {
+			return;
+		};
+
+		transition.elapsed += delta;
+
+		let next = (transition.elapsed >= transition.transition.fade_out)
+			.then(|| transition.next.take())
+			.flatten();
+
+		if transition.is_finished() {
+			self.outputs.get_mut(&output).unwrap().transition = None;
+		}
+
+		if let Some(next) = next {
+			self.swap_scene(output, next);
+		}
+	}
+
+	/// Returns the scene's active camera.
+	fn process_scene(&mut self, loaded: &mut LoadedScene, queue: &mut Box<dyn RenderQueue>, delta: Duration) -> Option<fatum_graphics::Camera> {
+		// the visibility of its objects is set below
+		loaded.visible = true;
+		loaded.apply_changes(queue);
+
+		let scene = loaded.scene.clone();
+
+		if let Ok(mut scene) = scene.try_write() {
+			self.schedule.run_stage(Stage::PreUpdate, &mut scene, delta);
+		} else {
+			log::warn!("Cannot process scene: could not get a write lock");
+			return None;
+		}
+
+		let nodes: Vec<NodeId>;
+
+		if let Ok(scene) = scene.try_read() {
+			nodes = scene.process_order(self.paused);
+
+			for node in &nodes {
+				scene.node(*node).unwrap().emit_key(Node::UPDATE, delta);
+			}
+		} else {
+			log::warn!("Cannot process scene: could not get a read lock");
+			return None;
+		}
+
+		let Ok(mut scene) = scene.try_write() else {
+			log::warn!("Cannot process scene: could not get a write lock");
+			return None;
+		};
+
+		for node in &nodes {
+			let node = scene.node_mut(*node).unwrap();
+			node.emit_mut_key(Node::UPDATE_MUT, delta);
+		}
+
+		for stage in [Stage::Update, Stage::PostUpdate, Stage::PreRender] {
+			self.schedule.run_stage(stage, &mut scene, delta);
+		}
+
+		// nothing iterates the scene anymore, and their models leave the queue before it's drawn
+		if scene.free_queued() > 0 {
+			loaded.apply_changes(queue);
+		}
+
+		// nodes that joined or left groups this frame are announced before the next one
+		scene.update_indices();
+
+		for (node, global_matrix) in self.moved.borrow_mut().drain(..) {
+			if let Some(object) = loaded.objects.get(&node) {
+				queue.set_object_matrix(object, global_matrix);
+			}
+		}
+
+		// disabled nodes and everything below them aren't drawn
+		for (id, object) in &loaded.objects {
+			queue.set_object_visible(object, scene.is_enabled_in_tree(*id));
+		}
+
+		scene.query::<components::Camera>()
+			.find(|(id, camera)| camera.is_active() && scene.is_enabled_in_tree(*id))
+			.map(|(_, camera)| camera.into())
+	}
 }
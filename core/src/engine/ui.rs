@@ -6,6 +6,7 @@ use egui_glow::{EguiGlow, ShaderVersion};
 use fatum_graphics::platform::{GraphicsContext, GraphicsPlatform, PlatformId};
 use fatum_graphics::platform::opengl::OpenGlPlatform;
 use fatum_scene::iterators::{SceneDfsIterator, ScenePostDfsIterator};
+use fatum_scene::NodeId;
 use winit::event::WindowEvent;
 use winit::event_loop::{ActiveEventLoop, EventLoop};
 use winit::window::{Window, WindowId};
@@ -65,34 +66,51 @@ impl<P> UiEngine<P> where P: GraphicsPlatform {
 		}
 
 		let queue_index = queue_index.unwrap();
-		let scene = scene_engine.scene(queue_index);
+		let scenes = scene_engine.active_scenes(queue_index);
+		let fade = scene_engine.fade(queue_index);
 
-		if scene.is_none() {
+		if scenes.is_empty() && fade.is_none() {
 			log::debug!("Queue {:?} does not have any scene active", queue_index);
 			return false;
 		}
 
-		let scene = scene.unwrap();
 		let window = graphics_engine.window(window).unwrap().wimpl();
+		let mut graphs = Vec::with_capacity(scenes.len());
 
-		let nodes: Vec<u32> = SceneDfsIterator::new(scene.clone(), Default::default())
-			.collect();
+		for scene in &scenes {
+			let nodes: Vec<NodeId> = SceneDfsIterator::new(scene.clone(), Default::default())
+				.collect();
 
-		if let Ok(scene) = scene.try_read() {
-			if let Some(ui) = self.ui_glow.as_mut() {
-				ui.run(window, move |ctx| {
-					for node in &nodes {
+			if let Ok(scene) = scene.try_read() {
+				graphs.push((scene, nodes));
+			} else {
+				log::warn!("Could not get a read lock on a scene of queue {}; its UI nodes will not be processed", queue_index);
+			}
+		}
+
+		if let Some(ui) = self.ui_glow.as_mut() {
+			ui.run(window, move |ctx| {
+				for (scene, nodes) in &graphs {
+					for node in nodes {
 						let node = scene.node(*node)
 							.expect("Iterator returned a non-existing node");
 
-						if let Some(element) = node.component::<UiElement>() {
+						if let Some(element) = node.component::<UiElement>() && scene.is_enabled_in_tree(node.id()) {
 							element.draw(delta, ctx);
 						}
 					}
-				});
-			}
-		} else {
-			log::warn!("Could not get a read lock on scene {}; UI nodes will not be processed", queue_index);
+				}
+
+				// over everything, UI included
+				if let Some(fade) = fade {
+					let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0) as u8;
+
+					ctx.layer_painter(egui::LayerId::new(egui::Order::Tooltip, egui::Id::new("scene_transition")))
+						.rect_filled(ctx.content_rect(), 0.0, egui::Color32::from_rgba_unmultiplied(
+							channel(fade.r), channel(fade.g), channel(fade.b), channel(fade.a)
+						));
+				}
+			});
 		}
 
 		if let Some(ui) = self.ui_glow.as_mut() {
@@ -1,2 +1,4 @@
 mod mat4;
 pub use mat4::*;
+
+pub mod reflect;
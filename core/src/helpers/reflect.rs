@@ -0,0 +1,35 @@
+//! `#[reflect(with = "...")]` conversions for engine types.
+
+/// `fatum_graphics::Color` as `Value::Color`.
+pub mod color {
+	use fatum_graphics::Color;
+	use fatum_scene::reflect::{Value, ValueKind};
+	use glam::Vec4;
+
+	pub const KIND: ValueKind = ValueKind::Color;
+
+	pub fn to_value(color: &Color) -> Value {
+		Value::Color(Vec4::new(color.r, color.g, color.b, color.a))
+	}
+
+	pub fn from_value(value: Value) -> Option<Color> {
+		match value {
+			Value::Color(color) => Some(Color::from_rgba_f32(color.x, color.y, color.z, color.w)),
+			_ => None
+		}
+	}
+}
+
+/// Textures as `Value::Resource`, read-only since loading one needs `Resources`.
+pub mod texture {
+	use fatum_resources::ResourceRef;
+	use fatum_scene::reflect::{Value, ValueKind};
+
+	use crate::resources::ResTexture2D;
+
+	pub const KIND: ValueKind = ValueKind::Resource;
+
+	pub fn to_value(texture: &ResourceRef<ResTexture2D>) -> Value {
+		Value::Resource(texture.borrow().path().clone())
+	}
+}
@@ -21,7 +21,7 @@ impl Camera2D {
 		let mut node = Node::new();
 
 		let c2d = Box::new(Self {
-			owner: Default::default(),
+			owner: NodeId::INVALID,
 			scene: Default::default(),
 			size
 		});
@@ -24,7 +24,7 @@ impl Camera3D {
 		let mut node = Node::new();
 
 		let c3d = Box::new(Self {
-			owner: Default::default(),
+			owner: NodeId::INVALID,
 			scene: Default::default(),
 			size,
 			fov
@@ -36,6 +36,7 @@ pub struct ResTexture2D {
 
 impl ResTexture2D {
 	pub fn get(&self) -> &Box<dyn Texture2D> { &self.value }
+	pub fn path(&self) -> &PathBuf { &self.path }
 }
 
 impl<P: GraphicsPlatform + ResourcePlatform + Sized> Resource<P> for ResTexture2D {
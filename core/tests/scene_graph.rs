@@ -53,11 +53,11 @@ impl<P: GraphicsPlatform + ResourcePlatform + Clone> Application<P> for SceneGra
 			{
 				let sprite = scene.node_mut(sprite).unwrap();
 
-				sprite.connect("ready", |args: &(*const Node, ())| {
+				sprite.connect_key(Node::READY, |args| {
 					log::info!("sprite2 is ready!");
 				});
 
-				sprite.connect_mut("$update", |args: &(*mut Node, std::time::Duration)| {
+				sprite.connect_mut_key(Node::UPDATE_MUT, |args| {
 					let node = unsafe { &mut *args.0 };
 
 					node.component_mut::<Transform2D>().unwrap()
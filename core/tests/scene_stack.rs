@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use fatum::{Application, ApplicationInfo, CoreEngine, OutputKind, SceneId, Transition, components::Transform2D, nodes::{Camera2D, Sprite2D, UiWindow}, resources::ResTexture2D};
+use fatum_graphics::{platform::{GraphicsPlatform, opengl::OpenGlPlatform}, render::PipelineKind};
+use fatum_resources::{ResourcePlatform, ResourceRef};
+use fatum_scene::{SceneGraph, SharedSceneGraph};
+use glam::{UVec2, Vec2};
+use winit::{event_loop::EventLoop, platform::x11::EventLoopBuilderExtX11};
+
+struct SceneStackApplication<P: GraphicsPlatform + ResourcePlatform> {
+	texture: Option<ResourceRef<ResTexture2D>>,
+	elapsed: Duration,
+	level: usize,
+	pause_menu: Option<SceneId>,
+	_marker: std::marker::PhantomData<P>
+}
+
+impl<P: GraphicsPlatform + ResourcePlatform> SceneStackApplication<P> {
+	fn level(&self, translation: Vec2) -> SharedSceneGraph {
+		let scene = SceneGraph::new();
+
+		{
+			let mut scene = scene.write().unwrap();
+
+			let mut sprite = Sprite2D::new(self.texture.clone().unwrap());
+			sprite.component_mut::<Transform2D>().unwrap()
+				.set_translation(translation);
+			sprite.component_mut::<Transform2D>().unwrap()
+				.set_scale(Vec2::new(200.0, 120.0));
+			scene.add_node(sprite, None);
+
+			scene.add_node(Camera2D::new(UVec2::new(1024, 768), true), None);
+		}
+
+		scene
+	}
+}
+
+impl<P: GraphicsPlatform + ResourcePlatform + Clone> Application<P> for SceneStackApplication<P> {
+	fn info() -> ApplicationInfo {
+		ApplicationInfo {
+			name: String::from("Scene Stack")
+		}
+	}
+
+	fn setup(&mut self, engine: &mut CoreEngine<P, Self>, event_loop: &EventLoop<()>) where Self: Sized {
+		engine.graphics_engine().create_queue(0, PipelineKind::Default);
+		engine.graphics_engine().create_output(0, event_loop, OutputKind::Window);
+
+		self.texture = Some(engine.resource_engine().get().load_by_path::<ResTexture2D>("1.png", true).unwrap());
+
+		let level = self.level(Vec2::new(100.0, 60.0));
+		engine.scene_engine().set_scene(0, level);
+
+		// stays over every level
+		let hud = SceneGraph::new();
+		hud.write().unwrap().add_node(UiWindow::new(String::from("HUD"), |_, _, ui| {
+			ui.label("Level loaded");
+		}), None);
+		engine.scene_engine().add_scene(0, hud);
+
+		engine.scene_engine().scene_unloaded.connect(|args| {
+			log::info!("Unloaded {:?} from output {}", args.1, args.0);
+		});
+	}
+
+	fn process(&mut self, engine: &mut CoreEngine<P, Self>, delta: Duration) where Self: Sized {
+		let before = self.elapsed;
+		self.elapsed += delta;
+
+		let passed = |seconds: u64| before < Duration::from_secs(seconds) && self.elapsed >= Duration::from_secs(seconds);
+
+		if passed(2) || passed(5) {
+			self.level += 1;
+			let level = self.level(Vec2::new(100.0 + 150.0 * self.level as f32, 60.0));
+
+			assert!(engine.scene_engine().change_scene(0, level, Transition::fade(Duration::from_secs(1))));
+		}
+
+		if passed(7) {
+			let menu = SceneGraph::new();
+			menu.write().unwrap().add_node(UiWindow::new(String::from("Paused"), |_, _, ui| {
+				ui.heading("Paused");
+			}), None);
+
+			self.pause_menu = engine.scene_engine().push_scene(0, menu);
+			assert_eq!(engine.scene_engine().active_scenes(0).len(), 1);
+		}
+
+		if passed(9) {
+			assert!(engine.scene_engine().remove_scene(self.pause_menu.take().unwrap()).is_some());
+			assert_eq!(engine.scene_engine().active_scenes(0).len(), 2);
+		}
+	}
+}
+
+impl<P: GraphicsPlatform + ResourcePlatform> Default for SceneStackApplication<P> {
+	fn default() -> Self {
+		Self {
+			texture: None,
+			elapsed: Duration::ZERO,
+			level: 0,
+			pause_menu: None,
+			_marker: Default::default()
+		}
+	}
+}
+
+#[test]
+fn opengl_scene_stack() {
+	fatum::build::link_test_assets();
+
+	let event_loop = EventLoop::builder().with_any_thread(true).build().unwrap();
+
+	let app = Box::new(SceneStackApplication::<OpenGlPlatform>::default());
+	let mut engine = CoreEngine::<OpenGlPlatform, SceneStackApplication::<OpenGlPlatform>>::new(app, &event_loop);
+
+	engine.setup(&event_loop);
+	event_loop.run_app(&mut engine).unwrap();
+}
@@ -0,0 +1,102 @@
+use fatum::components::{self, Transform, Transform3D};
+use fatum_scene::{Node, NodeId, SceneGraph};
+use glam::{Mat4, Quat, Vec3};
+
+fn transform(x: f32) -> Box<Transform3D> {
+	Box::new(Transform3D::new(Vec3::new(x, 1.0, 0.0), Quat::from_rotation_y(0.1), Vec3::ONE))
+}
+
+fn global_matrix(graph: &SceneGraph, id: NodeId) -> Mat4 {
+	graph.node(id).unwrap().component::<Transform3D>().unwrap().global_matrix()
+}
+
+#[test]
+fn parallel_propagation() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+
+	// wide enough to be spread over threads, deep enough to chain
+	let mut leaves = Vec::new();
+
+	for i in 0..8 {
+		let mut branch = Node::with_name(&format!("Branch{}", i));
+		branch.add_component(transform(i as f32));
+		let branch = graph.add_node(branch, None);
+
+		for j in 0..100 {
+			let mut child = Node::with_name(&format!("Child{}", j));
+			child.add_component(transform(1.0));
+			let child = graph.add_node(child, Some(branch));
+
+			let mut leaf = Node::with_name("Leaf");
+			leaf.add_component(transform(2.0));
+			leaves.push((branch, child, graph.add_node(leaf, Some(child))));
+		}
+	}
+
+	// transforms below a node without one start over
+	let group = graph.add_node(Node::with_name("Group"), Some(leaves[0].2));
+	let mut loose = Node::with_name("Loose");
+	loose.add_component(transform(3.0));
+	let loose = graph.add_node(loose, Some(group));
+
+	let changed = components::propagate_transforms(&mut graph);
+	assert_eq!(changed.len(), 8 * 201 + 1);
+	assert_eq!(changed.last().unwrap().0, leaves.last().unwrap().2);
+
+	for (branch, child, leaf) in &leaves {
+		let expected = global_matrix(&graph, *branch) * transform(1.0).calculate_matrix() * transform(2.0).calculate_matrix();
+		assert!(global_matrix(&graph, *leaf).abs_diff_eq(expected, 1e-4));
+		assert!(global_matrix(&graph, *child).abs_diff_eq(global_matrix(&graph, *branch) * transform(1.0).calculate_matrix(), 1e-4));
+	}
+
+	assert_eq!(global_matrix(&graph, loose), transform(3.0).calculate_matrix());
+
+	// only what moved, and what's below it
+	assert!(components::propagate_transforms(&mut graph).is_empty());
+
+	let (_, child, leaf) = leaves[42];
+	graph.node_mut(child).unwrap().component_mut::<Transform3D>().unwrap().translate(Vec3::X);
+
+	let changed: Vec<NodeId> = components::propagate_transforms(&mut graph).into_iter().map(|(id, _)| id).collect();
+	assert_eq!(changed, vec![child, leaf]);
+}
+
+#[test]
+fn reparent() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+
+	let mut left = Node::with_name("Left");
+	left.add_component(transform(-5.0));
+	let left = graph.add_node(left, None);
+
+	let mut right = Node::with_name("Right");
+	right.add_component(transform(5.0));
+	let right = graph.add_node(right, None);
+
+	let mut child = Node::with_name("Child");
+	child.add_component(transform(1.0));
+	let child = graph.add_node(child, Some(left));
+
+	let mut leaf = Node::with_name("Leaf");
+	leaf.add_component(transform(2.0));
+	let leaf = graph.add_node(leaf, Some(child));
+
+	components::propagate_transforms(&mut graph);
+
+	// without keeping the global matrix, the node follows its new parent
+	assert!(graph.reparent(child, right));
+
+	let changed: Vec<NodeId> = components::propagate_transforms(&mut graph).into_iter().map(|(id, _)| id).collect();
+	assert_eq!(changed, vec![child, leaf]);
+
+	assert!(global_matrix(&graph, child).abs_diff_eq(transform(5.0).calculate_matrix() * transform(1.0).calculate_matrix(), 1e-4));
+	assert!(global_matrix(&graph, leaf).abs_diff_eq(global_matrix(&graph, child) * transform(2.0).calculate_matrix(), 1e-4));
+
+	// back to the root
+	let root = graph.root_id();
+	assert!(graph.reparent(child, root));
+	components::propagate_transforms(&mut graph);
+	assert_eq!(global_matrix(&graph, child), transform(1.0).calculate_matrix());
+}
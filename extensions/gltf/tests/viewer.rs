@@ -73,7 +73,7 @@ impl<P: GraphicsPlatform + ResourcePlatform + Clone> Application<P> for GltfView
 				let scene1 = scene.clone();
 
 				let mut node = UiWindow::new(String::from("Scene"), move |_, _, ui| {
-					let nodes: Vec<u32> = SceneDfsIterator::new(scene1.clone(), 0).collect();
+					let nodes: Vec<NodeId> = SceneDfsIterator::new(scene1.clone(), NodeId::default()).collect();
 					let scene = scene1.read().unwrap();
 
 					for id in nodes {
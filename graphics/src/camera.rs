@@ -1,8 +1,9 @@
 use bytemuck::{Pod, Zeroable};
 use glam::{Mat4, UVec2, Vec2, Vec3, Vec4};
+use serde::{Deserialize, Serialize};
 
 #[repr(C)]
-#[derive(Debug, Copy, Clone, Pod, Zeroable, PartialEq)]
+#[derive(Debug, Copy, Clone, Pod, Zeroable, PartialEq, Serialize, Deserialize)]
 pub struct Camera {
 	pub projection: Mat4,
 	pub inverse_projection: Mat4,
@@ -7,6 +7,7 @@ use std::{cell::RefCell, collections::HashMap, hash::Hash, num::{NonZero, NonZer
 struct ObjectData {
 	id: u64,
 	matrix: Mat4,
+	visible: bool,
 
 	gl: Arc<glow::Context>,
 	vaos: Vec<NativeVertexArray>,
@@ -92,7 +93,7 @@ impl RenderQueue for OpenGlRenderQueue {
 
 		pipeline.begin();
 
-		for (object, data) in &self.objects {
+		for (object, data) in self.objects.iter().filter(|(_, data)| data.visible) {
 			let meshes = &object.model.meshes;
 
 			pipeline.matrix_data().set_data(vec![data.matrix].into());
@@ -317,6 +318,7 @@ impl RenderQueue for OpenGlRenderQueue {
 			ObjectData {
 				id: object.id,
 				matrix,
+				visible: true,
 				gl: gl.clone(),
 				vaos,
 				vbos
@@ -335,6 +337,15 @@ impl RenderQueue for OpenGlRenderQueue {
 		false
 	}
 	
+	fn set_object_visible(&mut self, object: &RenderObject, visible: bool) -> bool {
+		if let Some(data) = self.objects.get_mut(object) {
+			data.visible = visible;
+			return true;
+		}
+
+		false
+	}
+
 	fn remove_object(&mut self, object: &RenderObject) -> bool {
 		self.objects.remove(object).is_some()
 	}
@@ -4,6 +4,8 @@ use glam::Mat4;
 
 use crate::Model;
 
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
 #[derive(Debug, Clone)]
 pub struct RenderObject {
 	pub id: u64,
@@ -11,10 +13,8 @@ pub struct RenderObject {
 }
 
 impl RenderObject {
-	const ID_COUNTER: AtomicU64 = AtomicU64::new(1);
-
 	pub fn new(model: Rc<Box<Model>>) -> Self {
-		Self::with_id(Self::ID_COUNTER.fetch_add(1, Ordering::Relaxed), model)
+		Self::with_id(NEXT_ID.fetch_add(1, Ordering::Relaxed), model)
 	}
 
 	pub fn with_id(id: u64, model: Rc<Box<Model>>) -> Self {
@@ -28,6 +28,8 @@ pub trait RenderQueue {
 
 	fn add_object(&mut self, object: &RenderObject, matrix: Mat4) -> bool;
 	fn set_object_matrix(&mut self, object: &RenderObject, matrix: Mat4) -> bool;
+	/// Hidden objects stay in the queue, but aren't drawn.
+	fn set_object_visible(&mut self, object: &RenderObject, visible: bool) -> bool;
 	fn remove_object(&mut self, object: &RenderObject) -> bool;
 	fn clear_objects(&mut self);
 }
@@ -0,0 +1,14 @@
+use std::{collections::HashSet, rc::Rc};
+
+use fatum_graphics::{Model, render::RenderObject};
+
+#[test]
+fn unique_ids() {
+	let model = Rc::new(Box::new(Model { meshes: Vec::new() }));
+
+	let ids: HashSet<u64> = (0..100).map(|_| RenderObject::new(model.clone()).id).collect();
+	assert_eq!(ids.len(), 100);
+
+	// objects sharing a model are still told apart
+	assert_ne!(RenderObject::new(model.clone()), RenderObject::new(model));
+}
@@ -1,12 +1,89 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, DeriveInput};
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr, Path};
 
-#[proc_macro_derive(NodeComponent)]
+/// `#[component(serialize)]` also implements `SerializableComponent`, saving the component under its type name
+/// or the one given with `#[component(serialize = "...")]`. The type has to implement serde's traits itself.
+/// `#[component(reflect)]` exposes the component's `Reflect` implementation through `NodeComponent::reflect`.
+/// `#[component(no_clone)]` is for components that can't implement `Clone`, they're left out of copies of their node.
+/// `#[component(no_snapshot)]` leaves the component out of `SceneSnapshot`s.
+#[proc_macro_derive(NodeComponent, attributes(component))]
 pub fn derive_node_component(input: TokenStream) -> TokenStream {
 	let input = parse_macro_input!(input as DeriveInput);
 	let name = &input.ident;
 
+	let mut serialize_name: Option<String> = None;
+	let mut reflect = false;
+	let mut no_clone = false;
+	let mut no_snapshot = false;
+
+	for attr in &input.attrs {
+		if !attr.path().is_ident("component") {
+			continue;
+		}
+
+		let result = attr.parse_nested_meta(|meta| {
+			if meta.path.is_ident("serialize") {
+				serialize_name = Some(if meta.input.peek(syn::Token![=]) {
+					meta.value()?.parse::<LitStr>()?.value()
+				} else {
+					name.to_string()
+				});
+
+				return Ok(());
+			}
+
+			if meta.path.is_ident("reflect") {
+				reflect = true;
+				return Ok(());
+			}
+
+			if meta.path.is_ident("no_clone") {
+				no_clone = true;
+				return Ok(());
+			}
+
+			if meta.path.is_ident("no_snapshot") {
+				no_snapshot = true;
+				return Ok(());
+			}
+
+			Err(meta.error("unsupported component attribute"))
+		});
+
+		if let Err(e) = result {
+			return e.to_compile_error().into();
+		}
+	}
+
+	let serializable = serialize_name.map(|type_name| quote! {
+		impl fatum_scene::SerializableComponent for #name {
+			const TYPE_NAME: &'static str = #type_name;
+		}
+	});
+
+	let reflect = reflect.then(|| quote! {
+		fn reflect(&self) -> Option<&dyn fatum_scene::Reflect> {
+			Some(self)
+		}
+
+		fn reflect_mut(&mut self) -> Option<&mut dyn fatum_scene::Reflect> {
+			Some(self)
+		}
+	});
+
+	let snapshot = no_snapshot.then(|| quote! {
+		fn snapshot_mode(&self) -> fatum_scene::SnapshotMode {
+			fatum_scene::SnapshotMode::Skip
+		}
+	});
+
+	let clone = if no_clone {
+		quote! { None }
+	} else {
+		quote! { Some(std::boxed::Box::new(std::clone::Clone::clone(self))) }
+	};
+
 	let expanded = quote! {
 		impl fatum_scene::NodeComponent for #name {
 			fn name(&self) -> &str {
@@ -19,12 +96,12 @@ pub fn derive_node_component(input: TokenStream) -> TokenStream {
 			}
 
 			fn exit_scene(&mut self) {
-				self.owner = Default::default();
+				self.owner = fatum_scene::NodeId::INVALID;
 				self.scene = Default::default();
 			}
 
-			fn clone_component(&self) -> Box<dyn NodeComponent> {
-				std::boxed::Box::new(std::clone::Clone::clone(self))
+			fn clone_component(&self) -> Option<Box<dyn NodeComponent>> {
+				#clone
 			}
 
 			fn as_any(&self) -> &dyn std::any::Any {
@@ -34,6 +111,167 @@ pub fn derive_node_component(input: TokenStream) -> TokenStream {
 			fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
 				self
 			}
+
+			#reflect
+
+			#snapshot
+		}
+
+		#serializable
+	};
+
+	TokenStream::from(expanded)
+}
+
+struct ReflectField {
+	ident: syn::Ident,
+	ty: syn::Type,
+	read_only: bool,
+	set: Option<syn::Ident>,
+	with: Option<Path>
+}
+
+/// Implements `fatum_scene::Reflect`, see `fatum_scene::reflect` for the field attributes.
+#[proc_macro_derive(Reflect, attributes(reflect))]
+pub fn derive_reflect(input: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	let name = &input.ident;
+
+	let Data::Struct(data) = &input.data else {
+		return syn::Error::new_spanned(&input, "Reflect can only be derived for structs").to_compile_error().into();
+	};
+
+	let Fields::Named(fields) = &data.fields else {
+		return syn::Error::new_spanned(&input, "Reflect needs named fields").to_compile_error().into();
+	};
+
+	let mut reflected = Vec::new();
+
+	for field in &fields.named {
+		let ident = field.ident.clone().unwrap();
+
+		// the NodeComponent plumbing
+		if ident == "owner" || ident == "scene" {
+			continue;
+		}
+
+		let mut skip = false;
+		let mut reflect_field = ReflectField { ident, ty: field.ty.clone(), read_only: false, set: None, with: None };
+
+		for attr in &field.attrs {
+			if !attr.path().is_ident("reflect") {
+				continue;
+			}
+
+			let result = attr.parse_nested_meta(|meta| {
+				if meta.path.is_ident("skip") {
+					skip = true;
+				} else if meta.path.is_ident("read_only") {
+					reflect_field.read_only = true;
+				} else if meta.path.is_ident("set") {
+					reflect_field.set = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+				} else if meta.path.is_ident("with") {
+					reflect_field.with = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+				} else {
+					return Err(meta.error("unsupported reflect attribute"));
+				}
+
+				Ok(())
+			});
+
+			if let Err(e) = result {
+				return e.to_compile_error().into();
+			}
+		}
+
+		if !skip {
+			reflected.push(reflect_field);
+		}
+	}
+
+	let kind = |field: &ReflectField| {
+		let ty = &field.ty;
+
+		match &field.with {
+			Some(with) => quote! { #with::KIND },
+			None => quote! { <#ty as fatum_scene::reflect::ReflectValue>::KIND }
+		}
+	};
+
+	let infos = reflected.iter().map(|field| {
+		let field_name = field.ident.to_string();
+		let read_only = field.read_only;
+		let kind = kind(field);
+
+		quote! {
+			fatum_scene::reflect::FieldInfo { name: #field_name, kind: #kind, read_only: #read_only }
+		}
+	});
+
+	let getters = reflected.iter().map(|field| {
+		let ident = &field.ident;
+		let field_name = ident.to_string();
+		let ty = &field.ty;
+
+		let value = match &field.with {
+			Some(with) => quote! { #with::to_value(&self.#ident) },
+			None => quote! { <#ty as fatum_scene::reflect::ReflectValue>::to_value(&self.#ident) }
+		};
+
+		quote! { #field_name => Some(#value), }
+	});
+
+	let setters = reflected.iter().map(|field| {
+		let ident = &field.ident;
+		let field_name = ident.to_string();
+		let ty = &field.ty;
+
+		if field.read_only {
+			return quote! { #field_name => Err(fatum_scene::reflect::ReflectError::ReadOnly(#field_name)), };
+		}
+
+		let kind = kind(field);
+		let from_value = match &field.with {
+			Some(with) => quote! { #with::from_value(value.clone()) },
+			None => quote! { <#ty as fatum_scene::reflect::ReflectValue>::from_value(value.clone()) }
+		};
+
+		let assign = match &field.set {
+			Some(set) => quote! { self.#set(new_value) },
+			None => quote! { self.#ident = new_value }
+		};
+
+		quote! {
+			#field_name => {
+				let new_value: #ty = #from_value
+					.ok_or_else(|| fatum_scene::reflect::type_mismatch(#field_name, #kind, &value))?;
+
+				#assign;
+				Ok(())
+			}
+		}
+	});
+
+	let expanded = quote! {
+		impl fatum_scene::Reflect for #name {
+			fn fields(&self) -> &'static [fatum_scene::reflect::FieldInfo] {
+				const FIELDS: &[fatum_scene::reflect::FieldInfo] = &[#(#infos),*];
+				FIELDS
+			}
+
+			fn field(&self, name: &str) -> Option<fatum_scene::reflect::Value> {
+				match name {
+					#(#getters)*
+					_ => None
+				}
+			}
+
+			fn set_field(&mut self, name: &str, value: fatum_scene::reflect::Value) -> Result<(), fatum_scene::reflect::ReflectError> {
+				match name {
+					#(#setters)*
+					_ => Err(fatum_scene::reflect::ReflectError::NoSuchField(name.to_string()))
+				}
+			}
 		}
 	};
 
@@ -1,6 +1,6 @@
 use std::sync::{Arc, Mutex};
 
-use crate::{NodeId, SceneGraph, SharedSceneGraph};
+use crate::{NodeId, SceneGraph, SharedSceneGraph, SnapshotMode, reflect::Reflect};
 
 pub trait NodeComponent: 'static {
 	fn name(&self) -> &str;
@@ -8,8 +8,19 @@ pub trait NodeComponent: 'static {
 	fn enter_scene(&mut self, owner: NodeId, scene: SharedSceneGraph);
 	fn exit_scene(&mut self);
 
-	fn clone_component(&self) -> Box<dyn NodeComponent>;
+	/// `None` for components that can't be cloned, marked with `#[component(no_clone)]`.
+	fn clone_component(&self) -> Option<Box<dyn NodeComponent>>;
 
 	fn as_any(&self) -> &dyn std::any::Any;
 	fn as_any_mut(&mut self) -> &mut dyn std::any::Any;
+
+	/// The component's fields, for components marked with `#[component(reflect)]`.
+	fn reflect(&self) -> Option<&dyn Reflect> { None }
+	fn reflect_mut(&mut self) -> Option<&mut dyn Reflect> { None }
+
+	/// How `SceneSnapshot` captures the component, by its reflected fields if it has them and as a clone otherwise.
+	/// `#[component(no_snapshot)]` leaves it out.
+	fn snapshot_mode(&self) -> SnapshotMode {
+		if self.reflect().is_some() { SnapshotMode::Fields } else { SnapshotMode::Clone }
+	}
 }
@@ -0,0 +1,44 @@
+use std::{fmt, path::Path};
+
+#[derive(Debug, Clone)]
+pub enum ErrorKind {
+	IoError,
+	SerializationError,
+	DeserializationError,
+	UnknownComponent,
+	UncloneableComponent,
+	Other
+}
+
+#[derive(Debug, Clone)]
+pub struct SceneError {
+	kind: ErrorKind,
+	msg: String
+}
+
+impl SceneError {
+	pub fn new(kind: ErrorKind, msg: &str) -> Self {
+		Self {
+			kind,
+			msg: msg.to_string()
+		}
+	}
+
+	pub fn with_path<P: AsRef<Path>>(path: P, kind: ErrorKind, msg: &str) -> Self {
+		Self {
+			kind,
+			msg: format!("{} -> {}", path.as_ref().to_str().unwrap_or("Unknown"), msg)
+		}
+	}
+
+	pub fn kind(&self) -> &ErrorKind { &self.kind }
+	pub fn msg(&self) -> &str { &self.msg }
+}
+
+impl fmt::Display for SceneError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "Scene error {:?}: {}", self.kind, self.msg)
+	}
+}
+
+impl std::error::Error for SceneError {}
@@ -0,0 +1,48 @@
+use crate::{Node, NodeId, SceneGraph};
+
+impl SceneGraph {
+	/// Every node in the group, in the order they joined it.
+	pub fn nodes_in_group(&self, group: &str) -> Vec<NodeId> {
+		self.group_index().get(group).to_vec()
+	}
+
+	pub fn group_size(&self, group: &str) -> usize {
+		self.group_index().get(group).len()
+	}
+
+	/// Same as `Node::add_to_group`, but `node_added_to_group` is emitted right away.
+	pub fn add_to_group(&mut self, id: NodeId, group: &str) -> bool {
+		let Some(node) = self.node_mut(id) else { return false; };
+
+		let added = node.add_to_group(group);
+		self.update_indices();
+		added
+	}
+
+	/// Same as `Node::remove_from_group`, but `node_removed_from_group` is emitted right away.
+	pub fn remove_from_group(&mut self, id: NodeId, group: &str) -> bool {
+		let Some(node) = self.node_mut(id) else { return false; };
+
+		let removed = node.remove_from_group(group);
+		self.update_indices();
+		removed
+	}
+
+	/// Calls `f` on every node in the group. Nodes that join or leave the group in `f` don't change who's called.
+	pub fn call_group<F: FnMut(&mut Node)>(&mut self, group: &str, mut f: F) {
+		for id in self.nodes_in_group(group) {
+			if let Some(node) = self.node_mut(id) {
+				f(node);
+			}
+		}
+	}
+
+	/// Emits the signal on every node in the group that has it.
+	pub fn emit_group<Args: Clone + 'static>(&self, group: &str, name: &str, args: Args) {
+		for id in self.nodes_in_group(group) {
+			if let Some(node) = self.node(id) {
+				node.emit(name, args.clone());
+			}
+		}
+	}
+}
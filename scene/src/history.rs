@@ -0,0 +1,577 @@
+use std::{any::{Any, TypeId}, cell::{Cell, RefCell}, collections::HashMap, iter, mem, rc::Rc};
+
+use fatum_signals::ScopedConnection;
+
+use crate::{Node, NodeComponent, NodeId, NodeTree, SceneGraph, reflect::Value, tree};
+
+/// Undo and redo for edits to a scene, e.g. in an editor. Edits made through the history are recorded as steps
+/// that can be reverted. Edits made to the scene directly are only recorded while the history `watch`es it,
+/// otherwise steps on nodes removed that way fail.
+///
+/// Nodes that are removed and put back get new ids, `resolve` maps the ids they had before to the current ones.
+pub struct History {
+	undo: Vec<Edit>,
+	redo: Vec<Edit>,
+	ids: IdMap,
+
+	limit: usize,
+	// whether the next field change can merge into the last step
+	merging: bool,
+
+	captured: Rc<Captured>,
+	connections: Vec<ScopedConnection>
+}
+
+impl History {
+	pub fn new() -> Self {
+		Self::with_limit(usize::MAX)
+	}
+
+	/// Keeps at most `limit` steps to undo, dropping the oldest ones.
+	pub fn with_limit(limit: usize) -> Self {
+		Self {
+			undo: Vec::new(),
+			redo: Vec::new(),
+			ids: IdMap::default(),
+			limit,
+			merging: false,
+			captured: Rc::default(),
+			connections: Vec::new()
+		}
+	}
+
+	pub fn can_undo(&self) -> bool { !self.undo.is_empty() || self.captured.pending() }
+	// edits captured since clear what could be redone
+	pub fn can_redo(&self) -> bool { !self.redo.is_empty() && !self.captured.pending() }
+
+	pub fn clear(&mut self) {
+		self.undo.clear();
+		self.redo.clear();
+		self.ids = IdMap::default();
+		self.merging = false;
+		self.captured.take();
+	}
+
+	/// Records edits made to the scene directly as steps too, from its `node_added`, `node_removed`, `node_moved`,
+	/// `node_component_added` and `node_component_removed` signals, until `unwatch` or another `watch`. Nodes added
+	/// below a node that was just added, and their components, are part of its step.
+	///
+	/// Field changes have no signal, only the ones made through `set_field` are recorded. Neither are removed nodes
+	/// and components that can't be cloned, since they couldn't be put back.
+	pub fn watch(&mut self, scene: &mut SceneGraph) {
+		let captured = Rc::downgrade(&self.captured);
+		self.connections.clear();
+
+		self.connections.push(scene.node_added.connect_weak(captured.clone(), |captured, args| {
+			let (scene, node) = unsafe { (&*args.0, &*args.1) };
+			captured.node_added(scene, node.id());
+		}).scoped());
+
+		self.connections.push(scene.node_removed.connect_weak(captured.clone(), |captured, args| {
+			let (scene, node) = unsafe { (&*args.0, &*args.1) };
+			captured.node_removed(scene, node.id());
+		}).scoped());
+
+		self.connections.push(scene.node_moved.connect_weak(captured.clone(), |captured, args| {
+			let (scene, node) = unsafe { (&*args.0, &*args.1) };
+			captured.node_moved(scene, node.id(), (args.2, args.3));
+		}).scoped());
+
+		self.connections.push(scene.node_component_added.connect_weak(captured.clone(), |captured, args| {
+			let (node, component) = unsafe { (&*args.0, &*args.1) };
+			captured.component_added(node.id(), component.as_ref());
+		}).scoped());
+
+		self.connections.push(scene.node_component_removed.connect_weak(captured, |captured, args| {
+			let (node, component) = unsafe { (&*args.0, &*args.1) };
+			captured.component_removed(node.id(), component.as_ref());
+		}).scoped());
+	}
+
+	/// Stops recording edits made to the scene directly, see `watch`.
+	pub fn unwatch(&mut self) {
+		self.connections.clear();
+	}
+
+	/// The id a node that was put back by undo or redo has now.
+	pub fn resolve(&self, id: NodeId) -> NodeId {
+		self.ids.resolve(id)
+	}
+
+	/// Ends a continuous edit, so the next change to the same field gets a step of its own, e.g. when a drag ends.
+	pub fn seal(&mut self) {
+		self.merging = false;
+	}
+
+	/// Reverts the last step. Returns `false` if there's none, or if it can't be reverted anymore, in which case
+	/// it's dropped.
+	pub fn undo(&mut self, scene: &mut SceneGraph) -> bool {
+		self.flush();
+		let _paused = self.pause();
+
+		let Some(mut edit) = self.undo.pop() else { return false; };
+		self.merging = false;
+
+		if !edit.revert(scene, &mut self.ids) {
+			log::warn!("Can't undo {}, the scene changed outside of the history", edit.name());
+			return false;
+		}
+
+		self.redo.push(edit);
+		true
+	}
+
+	/// Makes the last undone step again, see `undo`.
+	pub fn redo(&mut self, scene: &mut SceneGraph) -> bool {
+		self.flush();
+		let _paused = self.pause();
+
+		let Some(mut edit) = self.redo.pop() else { return false; };
+
+		if !edit.apply(scene, &mut self.ids) {
+			log::warn!("Can't redo {}, the scene changed outside of the history", edit.name());
+			return false;
+		}
+
+		self.undo.push(edit);
+		true
+	}
+
+	/// See `SceneGraph::add_node`.
+	pub fn add_node(&mut self, scene: &mut SceneGraph, node: Node, parent: Option<NodeId>) -> NodeId {
+		self.flush();
+		let _paused = self.pause();
+
+		let id = scene.add_node(node, parent);
+		self.push(Edit::AddNode(Placement::of(scene, id)));
+		id
+	}
+
+	/// See `NodeTree::instantiate`.
+	pub fn add_tree(&mut self, scene: &mut SceneGraph, tree: &NodeTree, parent: Option<NodeId>) -> NodeId {
+		self.flush();
+		let _paused = self.pause();
+
+		let id = tree::insert_entry(scene, &tree.root, parent, usize::MAX);
+		self.push(Edit::AddNode(Placement::of(scene, id)));
+		id
+	}
+
+	/// See `SceneGraph::remove_node`. Nodes with components that can't be cloned aren't removed, since they
+	/// couldn't be put back.
+	pub fn remove_node(&mut self, scene: &mut SceneGraph, id: NodeId) -> bool {
+		if !scene.contains(id) || id == scene.root_id() {
+			return false;
+		}
+
+		self.record(scene, Edit::RemoveNode(Placement::of(scene, id)))
+	}
+
+	/// See `SceneGraph::reparent`.
+	pub fn reparent(&mut self, scene: &mut SceneGraph, node: NodeId, new_parent: NodeId) -> bool {
+		self.flush();
+		let _paused = self.pause();
+
+		if !scene.contains(node) || node == scene.root_id() {
+			return false;
+		}
+
+		let from = (scene.parent(node), scene.index_in_parent(node).unwrap());
+
+		if !scene.reparent(node, new_parent) {
+			return false;
+		}
+
+		let to = (new_parent, scene.index_in_parent(node).unwrap());
+		self.push(Edit::Move { node, from, to });
+		true
+	}
+
+	/// See `Node::add_component`.
+	pub fn add_component(&mut self, scene: &mut SceneGraph, node: NodeId, component: Box<dyn NodeComponent>) -> bool {
+		let type_id = Any::type_id(component.as_any());
+		self.record(scene, Edit::AddComponent(ComponentSlot { node, type_id, removed: Some(component) }))
+	}
+
+	/// See `Node::remove_component`.
+	pub fn remove_component<T: NodeComponent>(&mut self, scene: &mut SceneGraph, node: NodeId) -> bool {
+		self.record(scene, Edit::RemoveComponent(ComponentSlot { node, type_id: TypeId::of::<T>(), removed: None }))
+	}
+
+	/// Sets a field of the node's reflected component, see `Reflect::set_field`. Consecutive changes to the same
+	/// field merge into one step until `seal` is called.
+	pub fn set_field<T: NodeComponent>(&mut self, scene: &mut SceneGraph, node: NodeId, field: &str, value: Value) -> bool {
+		self.flush();
+		let _paused = self.pause();
+
+		let type_id = TypeId::of::<T>();
+
+		let Some(old) = scene.node(node)
+			.and_then(|node| node.component_of_type(type_id))
+			.and_then(|component| component.reflect()?.field(field))
+		else {
+			log::warn!("Node {} has no reflected {} with a field {}", node, std::any::type_name::<T>(), field);
+			return false;
+		};
+
+		if !set_field(scene, node, type_id, field, value.clone()) {
+			return false;
+		}
+
+		if self.merging && let Some(Edit::SetField { node: last, type_id: last_type, field: last_field, new, .. }) = self.undo.last_mut()
+			&& self.ids.resolve(*last) == node && *last_type == type_id && last_field == field
+		{
+			*new = value;
+			return true;
+		}
+
+		self.push(Edit::SetField { node, type_id, field: field.to_string(), old, new: value });
+		self.merging = true;
+		true
+	}
+
+	fn record(&mut self, scene: &mut SceneGraph, mut edit: Edit) -> bool {
+		self.flush();
+		let _paused = self.pause();
+
+		if !edit.apply(scene, &mut self.ids) {
+			return false;
+		}
+
+		self.push(edit);
+		true
+	}
+
+	/// Makes the edits captured from the watched scene steps.
+	fn flush(&mut self) {
+		for edit in self.captured.take() {
+			self.push(edit);
+		}
+	}
+
+	/// Edits made by the history itself aren't captured, they're recorded already.
+	fn pause(&self) -> Paused {
+		self.captured.paused.set(true);
+		Paused(self.captured.clone())
+	}
+
+	fn push(&mut self, edit: Edit) {
+		self.redo.clear();
+		self.undo.push(edit);
+		self.merging = false;
+
+		if self.undo.len() > self.limit {
+			self.undo.remove(0);
+		}
+	}
+}
+
+impl Default for History {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Edits made to the watched scene directly, until they're made steps at the next change through the history.
+#[derive(Default)]
+struct Captured {
+	edits: RefCell<Vec<Edit>>,
+	// the subtree added by the last edit, nodes added below it and changes to them are part of that edit
+	added: RefCell<Vec<NodeId>>,
+	paused: Cell<bool>
+}
+
+struct Paused(Rc<Captured>);
+
+impl Drop for Paused {
+	fn drop(&mut self) {
+		self.0.paused.set(false);
+	}
+}
+
+impl Captured {
+	fn pending(&self) -> bool {
+		!self.edits.borrow().is_empty()
+	}
+
+	fn take(&self) -> Vec<Edit> {
+		self.added.borrow_mut().clear();
+		mem::take(&mut self.edits.borrow_mut())
+	}
+
+	fn push(&self, edit: Edit) {
+		self.added.borrow_mut().clear();
+		self.edits.borrow_mut().push(edit);
+	}
+
+	fn is_added(&self, id: NodeId) -> bool {
+		self.added.borrow().contains(&id)
+	}
+
+	fn node_added(&self, scene: &SceneGraph, id: NodeId) {
+		if self.paused.get() {
+			return;
+		}
+
+		if !self.is_added(scene.parent(id)) {
+			self.push(Edit::AddNode(Placement::of(scene, id)));
+		}
+
+		self.added.borrow_mut().push(id);
+	}
+
+	fn node_removed(&self, scene: &SceneGraph, id: NodeId) {
+		if self.paused.get() {
+			return;
+		}
+
+		// children are removed right before their parents, last ones first, and go back along with them
+		let mut children = Vec::new();
+
+		{
+			let mut edits = self.edits.borrow_mut();
+
+			while edits.last().is_some_and(|edit| matches!(edit, Edit::RemoveNode(child) if child.parent == id)) {
+				if let Some(Edit::RemoveNode(child)) = edits.pop() {
+					children.push(child.detached);
+				}
+			}
+		}
+
+		// without a copy the step is still recorded, but can't be undone
+		let detached = children.into_iter().collect::<Option<Vec<Detached>>>()
+			.and_then(|children| match NodeTree::from_scene(scene, id) {
+				Ok(mut tree) => {
+					let mut ids = vec![id];
+
+					for child in children {
+						tree.root.children.push(child.tree.root);
+						ids.extend(child.ids);
+					}
+
+					Some(Detached { tree, ids })
+				},
+				Err(e) => {
+					log::warn!("Removing node {} can't be undone: {}", id, e);
+					None
+				}
+			});
+
+		self.push(Edit::RemoveNode(Placement { detached, ..Placement::of(scene, id) }));
+	}
+
+	fn node_moved(&self, scene: &SceneGraph, id: NodeId, from: (NodeId, usize)) {
+		let parent = scene.parent(id);
+
+		if self.paused.get() || self.is_added(id) && self.is_added(parent) {
+			return;
+		}
+
+		self.push(Edit::Move { node: id, from, to: (parent, scene.index_in_parent(id).unwrap()) });
+	}
+
+	fn component_added(&self, node: NodeId, component: &dyn NodeComponent) {
+		if self.paused.get() || self.is_added(node) {
+			return;
+		}
+
+		let type_id = Any::type_id(component.as_any());
+		self.push(Edit::AddComponent(ComponentSlot { node, type_id, removed: None }));
+	}
+
+	fn component_removed(&self, node: NodeId, component: &dyn NodeComponent) {
+		if self.paused.get() || self.is_added(node) {
+			return;
+		}
+
+		let Some(removed) = tree::copy_out_of_scene(component) else {
+			log::warn!("Can't record removing {} from node {}, it can't be cloned", component.name(), node);
+			return;
+		};
+
+		let type_id = Any::type_id(component.as_any());
+		self.push(Edit::RemoveComponent(ComponentSlot { node, type_id, removed: Some(removed) }));
+	}
+}
+
+/// Ids of removed nodes to the ids they got when they were put back.
+#[derive(Default)]
+struct IdMap(HashMap<NodeId, NodeId>);
+
+impl IdMap {
+	fn resolve(&self, mut id: NodeId) -> NodeId {
+		// ids aren't handed out twice, so this can't go in circles
+		while let Some(next) = self.0.get(&id) {
+			id = *next;
+		}
+
+		id
+	}
+}
+
+/// A change to the scene. Node ids are the ones the nodes had when it was recorded.
+enum Edit {
+	AddNode(Placement),
+	RemoveNode(Placement),
+	AddComponent(ComponentSlot),
+	RemoveComponent(ComponentSlot),
+	Move { node: NodeId, from: (NodeId, usize), to: (NodeId, usize) },
+	SetField { node: NodeId, type_id: TypeId, field: String, old: Value, new: Value }
+}
+
+impl Edit {
+	fn name(&self) -> &'static str {
+		match self {
+			Self::AddNode(_) => "adding a node",
+			Self::RemoveNode(_) => "removing a node",
+			Self::AddComponent(_) => "adding a component",
+			Self::RemoveComponent(_) => "removing a component",
+			Self::Move { .. } => "moving a node",
+			Self::SetField { .. } => "setting a field"
+		}
+	}
+
+	/// Makes the change, again or for the first time.
+	fn apply(&mut self, scene: &mut SceneGraph, ids: &mut IdMap) -> bool {
+		match self {
+			Self::AddNode(placement) => placement.insert(scene, ids),
+			Self::RemoveNode(placement) => placement.remove(scene, ids),
+			Self::AddComponent(slot) => slot.add(scene, ids),
+			Self::RemoveComponent(slot) => slot.remove(scene, ids),
+			Self::Move { node, to, .. } => move_to(scene, ids.resolve(*node), ids.resolve(to.0), to.1),
+			Self::SetField { node, type_id, field, new, .. } => set_field(scene, ids.resolve(*node), *type_id, field, new.clone())
+		}
+	}
+
+	fn revert(&mut self, scene: &mut SceneGraph, ids: &mut IdMap) -> bool {
+		match self {
+			Self::AddNode(placement) => placement.remove(scene, ids),
+			Self::RemoveNode(placement) => placement.insert(scene, ids),
+			Self::AddComponent(slot) => slot.remove(scene, ids),
+			Self::RemoveComponent(slot) => slot.add(scene, ids),
+			Self::Move { node, from, .. } => move_to(scene, ids.resolve(*node), ids.resolve(from.0), from.1),
+			Self::SetField { node, type_id, field, old, .. } => set_field(scene, ids.resolve(*node), *type_id, field, old.clone())
+		}
+	}
+}
+
+/// Where a node goes in the scene, and a copy of it while it's out.
+struct Placement {
+	id: NodeId,
+	parent: NodeId,
+	index: usize,
+	detached: Option<Detached>
+}
+
+struct Detached {
+	tree: NodeTree,
+	// ids the subtree had, in pre-order, to map to the ones it gets when it's put back
+	ids: Vec<NodeId>
+}
+
+impl Placement {
+	fn of(scene: &SceneGraph, id: NodeId) -> Self {
+		Self {
+			id,
+			parent: scene.parent(id),
+			index: scene.index_in_parent(id).unwrap(),
+			detached: None
+		}
+	}
+
+	fn insert(&mut self, scene: &mut SceneGraph, ids: &mut IdMap) -> bool {
+		let parent = ids.resolve(self.parent);
+
+		if !scene.contains(parent) {
+			return false;
+		}
+
+		let Some(detached) = self.detached.take() else { return false; };
+		let id = tree::insert_entry(scene, &detached.tree.root, Some(parent), self.index);
+
+		for (old, new) in detached.ids.into_iter().zip(iter::once(id).chain(scene.descendants(id))) {
+			ids.0.insert(old, new);
+		}
+
+		true
+	}
+
+	fn remove(&mut self, scene: &mut SceneGraph, ids: &mut IdMap) -> bool {
+		let id = ids.resolve(self.id);
+
+		if !scene.contains(id) {
+			return false;
+		}
+
+		let tree = match NodeTree::from_scene(scene, id) {
+			Ok(tree) => tree,
+			Err(e) => {
+				log::warn!("Can't take node {} out of the scene and put it back later: {}", id, e);
+				return false;
+			}
+		};
+
+		let subtree = iter::once(id).chain(scene.descendants(id)).collect();
+		scene.remove_node(id);
+
+		self.detached = Some(Detached { tree, ids: subtree });
+		true
+	}
+}
+
+/// A component on a node, and the component itself while it's off.
+struct ComponentSlot {
+	node: NodeId,
+	type_id: TypeId,
+	removed: Option<Box<dyn NodeComponent>>
+}
+
+impl ComponentSlot {
+	fn add(&mut self, scene: &mut SceneGraph, ids: &mut IdMap) -> bool {
+		let Some(node) = scene.node_mut(ids.resolve(self.node)) else { return false; };
+
+		if node.has_component_of_type(self.type_id) {
+			return false;
+		}
+
+		self.removed.take().is_some_and(|component| node.add_component(component))
+	}
+
+	fn remove(&mut self, scene: &mut SceneGraph, ids: &mut IdMap) -> bool {
+		let Some(node) = scene.node_mut(ids.resolve(self.node)) else { return false; };
+
+		self.removed = node.take_component(self.type_id);
+		self.removed.is_some()
+	}
+}
+
+fn move_to(scene: &mut SceneGraph, node: NodeId, parent: NodeId, index: usize) -> bool {
+	if !scene.contains(node) || node == scene.root_id() {
+		return false;
+	}
+
+	if scene.parent(node) != parent && !scene.reparent(node, parent) {
+		return false;
+	}
+
+	let from = scene.index_in_parent(node).unwrap();
+	let last = scene.children_slice(parent).len() - 1;
+	scene.move_child(parent, from, index.min(last))
+}
+
+fn set_field(scene: &mut SceneGraph, node: NodeId, type_id: TypeId, field: &str, value: Value) -> bool {
+	let Some(reflect) = scene.node_mut(node)
+		.and_then(|node| node.component_of_type_mut(type_id))
+		.and_then(|component| component.reflect_mut())
+	else {
+		return false;
+	};
+
+	match reflect.set_field(field, value) {
+		Ok(()) => true,
+		Err(e) => {
+			log::warn!("Failed to set {} of node {}: {}", field, node, e);
+			false
+		}
+	}
+}
@@ -0,0 +1,64 @@
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+use crate::NodeId;
+
+/// Which nodes have which keys, e.g. groups or component types, both ways around.
+pub(crate) struct MemberIndex<K> {
+	members: HashMap<K, Vec<NodeId>>,
+	keys: HashMap<NodeId, Vec<K>>
+}
+
+impl<K> Default for MemberIndex<K> {
+	fn default() -> Self {
+		Self { members: HashMap::new(), keys: HashMap::new() }
+	}
+}
+
+impl<K: Eq + Hash + Clone> MemberIndex<K> {
+	/// Updates the keys of the node, returns the ones it gained and the ones it lost.
+	pub(crate) fn set(&mut self, id: NodeId, keys: &[K]) -> (Vec<K>, Vec<K>) {
+		let old = self.keys.remove(&id).unwrap_or_default();
+
+		let lost: Vec<K> = old.iter().filter(|key| !keys.contains(key)).cloned().collect();
+		let gained: Vec<K> = keys.iter().filter(|key| !old.contains(key)).cloned().collect();
+
+		for key in &lost {
+			self.remove_member(key, id);
+		}
+
+		for key in &gained {
+			self.members.entry(key.clone()).or_default().push(id);
+		}
+
+		if !keys.is_empty() {
+			self.keys.insert(id, keys.to_vec());
+		}
+
+		(gained, lost)
+	}
+
+	/// Forgets the node, returns the keys it had.
+	pub(crate) fn remove(&mut self, id: NodeId) -> Vec<K> {
+		let keys = self.keys.remove(&id).unwrap_or_default();
+
+		for key in &keys {
+			self.remove_member(key, id);
+		}
+
+		keys
+	}
+
+	pub(crate) fn get<Q: Eq + Hash + ?Sized>(&self, key: &Q) -> &[NodeId] where K: Borrow<Q> {
+		self.members.get(key).map_or(&[], |ids| ids.as_slice())
+	}
+
+	fn remove_member(&mut self, key: &K, id: NodeId) {
+		if let Some(ids) = self.members.get_mut(key) {
+			ids.retain(|other| *other != id);
+
+			if ids.is_empty() {
+				self.members.remove(key);
+			}
+		}
+	}
+}
@@ -1,4 +1,7 @@
 pub mod iterators;
+pub mod error;
+pub mod reflect;
+pub use reflect::Reflect;
 
 mod scene;
 pub use scene::*;
@@ -12,6 +15,34 @@ pub use tree::*;
 mod component;
 pub use component::*;
 
+mod path;
+pub use path::matches_pattern;
+
+mod group;
+
+mod query;
+pub use query::*;
+
+mod schedule;
+pub use schedule::*;
+
+mod history;
+pub use history::*;
+
+mod process;
+pub use process::*;
+
+mod snapshot;
+pub use snapshot::*;
+
+mod index;
+
+mod serialize;
+pub use serialize::*;
+
+mod prefab;
+pub use prefab::*;
+
 mod base;
 pub use base::*;
 
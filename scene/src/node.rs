@@ -1,51 +1,109 @@
-use std::{any::Any, collections::HashMap, fmt::Debug, rc::Rc, sync::{Arc, Mutex, atomic::Ordering}};
+use std::{any::{Any, TypeId}, collections::HashMap, fmt::Debug, rc::Rc, sync::{Arc, Mutex, atomic::Ordering}};
 
-use fatum_signals::{Signal, SignalDispatcher, StaticSignal};
+use fatum_signals::{ConnectOptions, Connection, ConnectionId, Executor, SharedSignal, Signal, SignalDispatcher, SignalFuture, SignalKey, SignalQueue, SignalSnapshot, StaticSignal, TaskHandle, WeakCapture};
 use rand::{Rng, distr::{Alphabetic, SampleString}};
 
-use crate::{NodeComponent, SceneGraph, SharedSceneGraph};
+use crate::{NodeComponent, ProcessMode, ProcessSettings, SceneGraph, SharedSceneGraph, IndexQueue, RemovalQueue};
 
-pub type NodeId = u32;
+/// Identifies a node in a `SceneGraph`. Slots of removed nodes are reused, but with a new generation,
+/// so ids of removed nodes never resolve to a different node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct NodeId {
+	index: u32,
+	generation: u32
+}
+
+impl NodeId {
+	/// The id of nodes that aren't in a scene.
+	pub const INVALID: Self = Self { index: u32::MAX, generation: u32::MAX };
+
+	pub(crate) const fn new(index: u32, generation: u32) -> Self {
+		Self { index, generation }
+	}
+
+	pub fn index(&self) -> u32 { self.index }
+	pub fn generation(&self) -> u32 { self.generation }
+
+	/// Packs the id into a `u64`, generation in the high bits.
+	pub fn to_bits(&self) -> u64 {
+		(self.generation as u64) << 32 | self.index as u64
+	}
+
+	pub fn from_bits(bits: u64) -> Self {
+		Self::new(bits as u32, (bits >> 32) as u32)
+	}
+}
+
+impl std::fmt::Display for NodeId {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}v{}", self.index, self.generation)
+	}
+}
+
+/// Key of a node signal created with `Node::create_signal`, handlers get the emitting node along with the arguments.
+pub type NodeSignalKey<Args> = SignalKey<(*const Node, Args)>;
+/// Key of a node signal created with `Node::create_signal_mut`.
+pub type NodeSignalKeyMut<Args> = SignalKey<(*mut Node, Args)>;
 
 pub struct Node {
 	id: NodeId,
 	name: String,
 	
 	scene: Option<SharedSceneGraph>,
+	pub(crate) changed: Option<IndexQueue>,
+	pub(crate) removals: Option<RemovalQueue>,
+	groups: Vec<String>,
+	process: ProcessSettings,
 	components: Vec<Box<dyn NodeComponent>>,
+	// where each component type is in `components`
+	component_slots: HashMap<TypeId, usize>,
 
 	pub component_added: StaticSignal<(*const Node, *const Box<dyn NodeComponent>)>,
 	pub component_removed: StaticSignal<(*const Node, *const Box<dyn NodeComponent>)>,
 
-	signals: HashMap<String, Box<dyn Signal>>
+	signals: HashMap<String, Box<dyn Signal>>,
+	tasks: Vec<TaskHandle>
 }
 
 impl Node {
+	pub const ENTER_TREE: NodeSignalKey<()> = SignalKey::new("enter_tree");
+	pub const EXIT_TREE: NodeSignalKey<()> = SignalKey::new("exit_tree");
+	pub const READY: NodeSignalKey<()> = SignalKey::new("ready");
+
+	pub const UPDATE: NodeSignalKey<std::time::Duration> = SignalKey::new("update");
+	pub const UPDATE_MUT: NodeSignalKeyMut<std::time::Duration> = SignalKey::new("$update");
+
 	pub fn new() -> Self {
 		Self::with_name(&Alphabetic.sample_string(&mut rand::rng(), 12))
 	}
 
 	pub fn with_name(name: &str) -> Self {
-		Self::with_id_name(u32::MAX, name)
+		Self::with_id_name(NodeId::INVALID, name)
 	}
 
-	pub fn with_id_name(id: u32, name: &str) -> Self {
+	pub fn with_id_name(id: NodeId, name: &str) -> Self {
 		let mut this = Self {
 			id,
 			name: name.to_string(),
 			scene: None,
+			changed: None,
+			removals: None,
+			groups: Vec::new(),
+			process: ProcessSettings::default(),
 			components: vec![],
+			component_slots: HashMap::new(),
 			component_added: StaticSignal::new(),
 			component_removed: StaticSignal::new(),
-			signals: HashMap::new()
+			signals: HashMap::new(),
+			tasks: Vec::new()
 		};
 
-		this.create_signal::<()>("enter_tree");
-		this.create_signal::<()>("exit_tree");
-		this.create_signal::<()>("ready");
+		this.create_signal::<()>(Self::ENTER_TREE.name());
+		this.create_signal::<()>(Self::EXIT_TREE.name());
+		this.create_signal::<()>(Self::READY.name());
 
-		this.create_signal::<std::time::Duration>("update");
-		this.create_signal_mut::<std::time::Duration>("$update");
+		this.create_signal::<std::time::Duration>(Self::UPDATE.name());
+		this.create_signal_mut::<std::time::Duration>(Self::UPDATE_MUT.name());
 
 		this
 	}
@@ -53,60 +111,186 @@ impl Node {
 	pub fn id(&self) -> NodeId { self.id }
 
 	pub fn name(&self) -> &str { &self.name }
-	pub fn set_name(&mut self, name: &str) { self.name = name.to_string() }
+	pub fn set_name(&mut self, name: &str) {
+		self.name = name.to_string();
+		self.mark_changed();
+	}
 
-	pub fn scene(&self) -> Option<SharedSceneGraph> { self.scene.clone() }
-	pub fn parent(&self) -> NodeId { self.scene.as_ref().unwrap().read().unwrap().parent(self.id) }
-	pub fn children(&self) -> Vec<u32> { self.scene.as_ref().unwrap().read().unwrap().children(self.id) }
+	/// The groups the node is in, in the order it joined them.
+	pub fn groups(&self) -> &[String] { &self.groups }
+	pub fn is_in_group(&self, group: &str) -> bool { self.groups.iter().any(|other| other == group) }
 
-	pub fn component<T: NodeComponent>(&self) -> Option<&T> {
-		// TODO store in a HashMap for fast lookup
-		for component in &self.components {
-			let component_any = component.as_any();
+	/// Returns `false` if the node already is in the group. The scene emits `node_added_to_group` at its next
+	/// `update_indices`, or right away through `SceneGraph::add_to_group`.
+	pub fn add_to_group(&mut self, group: &str) -> bool {
+		if self.is_in_group(group) {
+			return false;
+		}
 
-			if component_any.is::<T>() {
-				return component_any.downcast_ref();
-			}
+		self.groups.push(group.to_string());
+		self.mark_changed();
+		true
+	}
+
+	pub fn remove_from_group(&mut self, group: &str) -> bool {
+		let Some(index) = self.groups.iter().position(|other| other == group) else { return false; };
+
+		self.groups.remove(index);
+		self.mark_changed();
+		true
+	}
+
+	fn mark_changed(&self) {
+		if let Some(changed) = &self.changed {
+			changed.lock().unwrap().push(self.id);
 		}
+	}
+
+	/// Removes the node and its children once it's safe to, see `SceneGraph::queue_remove`. Works from the node's
+	/// own update handlers, where the scene is locked.
+	pub fn queue_free(&self) {
+		let Some(removals) = &self.removals else {
+			log::warn!("{:?} isn't in a scene, there's nothing to remove it from", self);
+			return;
+		};
+
+		let mut removals = removals.lock().unwrap();
+
+		if !removals.contains(&self.id) {
+			removals.push(self.id);
+		}
+	}
 
-		None
+	pub fn is_enabled(&self) -> bool { self.process.enabled }
+	/// Disabled nodes don't update or render, along with everything below them. See `SceneGraph::is_enabled_in_tree`.
+	pub fn set_enabled(&mut self, enabled: bool) { self.process.enabled = enabled; }
+
+	pub fn process_mode(&self) -> ProcessMode { self.process.mode }
+	pub fn set_process_mode(&mut self, mode: ProcessMode) { self.process.mode = mode; }
+
+	pub fn process_priority(&self) -> i32 { self.process.priority }
+	/// Nodes with a lower priority update first, see `SceneGraph::process_order`.
+	pub fn set_process_priority(&mut self, priority: i32) { self.process.priority = priority; }
+
+	pub fn process_settings(&self) -> ProcessSettings { self.process }
+	pub fn set_process_settings(&mut self, settings: ProcessSettings) { self.process = settings; }
+
+	pub fn scene(&self) -> Option<SharedSceneGraph> { self.scene.clone() }
+	pub fn parent(&self) -> NodeId { self.scene.as_ref().unwrap().read().unwrap().parent(self.id) }
+	pub fn children(&self) -> Vec<NodeId> { self.scene.as_ref().unwrap().read().unwrap().children(self.id) }
+
+	/// Looks up a node relative to this one, e.g. `"../Weapon"`. See `SceneGraph::get_node_from`.
+	pub fn get_node(&self, path: &str) -> Option<NodeId> { self.scene.as_ref()?.read().unwrap().get_node_from(self.id, path) }
+	pub fn get_nodes(&self, path: &str) -> Vec<NodeId> {
+		self.scene.as_ref().map_or_else(Vec::new, |scene| scene.read().unwrap().get_nodes_from(self.id, path))
+	}
+	pub fn path(&self) -> Option<String> { self.scene.as_ref()?.read().unwrap().path_of(self.id) }
+
+	pub fn component<T: NodeComponent>(&self) -> Option<&T> {
+		let slot = *self.component_slots.get(&TypeId::of::<T>())?;
+		self.components[slot].as_any().downcast_ref()
 	}
 
 	pub fn component_mut<T: NodeComponent>(&mut self) -> Option<&mut T> {
-		for component in &mut self.components {
-			let component_any = component.as_any_mut();
+		let slot = *self.component_slots.get(&TypeId::of::<T>())?;
+		self.components[slot].as_any_mut().downcast_mut()
+	}
 
-			if component_any.is::<T>() {
-				return component_any.downcast_mut();
-			}
-		}
+	pub fn has_component<T: NodeComponent>(&self) -> bool {
+		self.has_component_of_type(TypeId::of::<T>())
+	}
 
-		None
+	pub(crate) fn has_component_of_type(&self, type_id: TypeId) -> bool {
+		self.component_slots.contains_key(&type_id)
 	}
 
 	pub fn components(&self) -> &Vec<Box<dyn NodeComponent>> { &self.components }
-	// TODO check if component already exists
-	pub fn add_component(&mut self, mut component: Box<dyn NodeComponent>) {
+
+	/// Adds the component, unless the node already has one of the same type. Returns whether it was added.
+	pub fn add_component(&mut self, mut component: Box<dyn NodeComponent>) -> bool {
+		let type_id = Any::type_id(component.as_any());
+
+		if self.component_slots.contains_key(&type_id) {
+			log::warn!("{:?} already has a {}, not adding another one", self, component.name());
+			return false;
+		}
+
 		if let Some(scene) = &self.scene {
 			component.enter_scene(self.id, scene.clone());
 		}
 
 		self.component_added.emit((self, &component));
+		self.component_slots.insert(type_id, self.components.len());
 		self.components.push(component);
+		self.mark_changed();
+		true
 	}
 
 	pub fn remove_component<T: NodeComponent>(&mut self) -> bool {
-		for i in 0..self.components.len() {
-			let component = &self.components[i];
+		self.remove_component_of_type(TypeId::of::<T>())
+	}
+
+	pub(crate) fn remove_component_of_type(&mut self, type_id: TypeId) -> bool {
+		self.take_component(type_id).is_some()
+	}
+
+	/// Removes the component and hands it back, out of the scene.
+	pub(crate) fn take_component(&mut self, type_id: TypeId) -> Option<Box<dyn NodeComponent>> {
+		let slot = self.component_slots.remove(&type_id)?;
 
-			if component.as_any().is::<T>() {
-				self.component_removed.emit((self, component));
-				self.components.remove(i);
-				return true;
+		self.component_removed.emit((self, &self.components[slot]));
+		let mut component = self.components.remove(slot);
+		component.exit_scene();
+
+		for other in self.component_slots.values_mut() {
+			if *other > slot {
+				*other -= 1;
 			}
 		}
 
-		false
+		self.mark_changed();
+		Some(component)
+	}
+
+	/// Removes every component, emitting `component_removed` for each, and takes them out of the scene.
+	pub(crate) fn clear_components(&mut self) {
+		for component in &self.components {
+			self.component_removed.emit((self, component));
+		}
+
+		for mut component in self.components.drain(..) {
+			component.exit_scene();
+		}
+
+		self.component_slots.clear();
+		self.mark_changed();
+	}
+
+	pub(crate) fn component_of_type(&self, type_id: TypeId) -> Option<&dyn NodeComponent> {
+		let slot = *self.component_slots.get(&type_id)?;
+		Some(self.components[slot].as_ref())
+	}
+
+	pub(crate) fn component_of_type_mut(&mut self, type_id: TypeId) -> Option<&mut Box<dyn NodeComponent>> {
+		let slot = *self.component_slots.get(&type_id)?;
+		Some(&mut self.components[slot])
+	}
+
+	/// The types of the node's components, in the order they were added.
+	pub(crate) fn component_types(&self) -> Vec<TypeId> {
+		self.components.iter().map(|component| Any::type_id(component.as_any())).collect()
+	}
+
+	/// Mutable references to the components of the given types, `None` if the node is missing one of them or a
+	/// type is given twice.
+	pub(crate) fn components_disjoint_mut<const N: usize>(&mut self, types: [TypeId; N]) -> Option<[&mut Box<dyn NodeComponent>; N]> {
+		let mut slots = [0; N];
+
+		for (slot, type_id) in slots.iter_mut().zip(types) {
+			*slot = *self.component_slots.get(&type_id)?;
+		}
+
+		self.components.get_disjoint_mut(slots).ok()
 	}
 
 	pub fn enter_scene(&mut self, id: NodeId, scene: SharedSceneGraph) {
@@ -117,14 +301,20 @@ impl Node {
 			component.enter_scene(id, scene.clone());
 		}
 
-		self.emit("enter_scene", ());
+		self.emit_key(Self::ENTER_TREE, ());
 	}
 
 	pub fn exit_scene(&mut self) {
-		self.emit("exit_scene", ());
+		self.emit_key(Self::EXIT_TREE, ());
+
+		for task in self.tasks.drain(..) {
+			task.cancel();
+		}
 
-		self.id = 0;
+		self.id = NodeId::INVALID;
 		self.scene = None;
+		self.changed = None;
+		self.removals = None;
 		
 		self.component_added.clear();
 		self.component_removed.clear();
@@ -135,52 +325,54 @@ impl Node {
 	}
 
 	pub fn ready(&self) {
-		self.emit("ready", ());
+		self.emit_key(Self::READY, ());
 	}
 
+	/// Spawns a coroutine on the `Executor` that is cancelled when this node exits the scene.
+	pub fn spawn<F: Future<Output = ()> + 'static>(&mut self, future: F) -> TaskHandle {
+		self.tasks.retain(|task| !task.is_finished());
+
+		let task = Executor::spawn(future);
+		self.tasks.push(task.clone());
+		task
+	}
+
+	pub fn tasks(&self) -> &[TaskHandle] { &self.tasks }
+
 	pub fn as_any(&self) -> &dyn std::any::Any { self }
 
 	// signals (kinda messy :/)
 	pub fn create_signal<Args: 'static>(&mut self, name: &str) {
-		let signal = StaticSignal::<(*const Self, Args)>::new();
+		let signal = StaticSignal::<(*const Self, Args)>::with_name(name);
 		self.signals.insert(name.to_string(), Box::new(signal));
 	}
 
 	pub fn create_signal_mut<Args: 'static>(&mut self, name: &str) {
-		let signal_mut = StaticSignal::<(*mut Self, Args)>::new();
+		let signal_mut = StaticSignal::<(*mut Self, Args)>::with_name(name);
 		self.signals.insert(name.to_string(), Box::new(signal_mut));
 	}
 
-	pub fn connect<Args: 'static, F: Fn(&(*const Self, Args)) -> () + 'static>(&mut self, name: &str, handler: F) {
+	pub fn connect<Args: 'static, F: Fn(&(*const Self, Args)) -> () + 'static>(&mut self, name: &str, handler: F) -> Connection {
 		let signal = self.signals.get_mut(&name.to_string())
 			.expect(format!("No such signal: {}", name).as_str());
 
 		let handler = Box::new(Box::new(handler) as Box<dyn Fn(&(*const Self, Args))>) as Box<dyn Any>;
-		signal.connect_any(handler);
+		signal.connect_any(handler)
 	}
 
-	pub fn connect_mut<Args: 'static, F: Fn(&(*mut Self, Args)) -> () + 'static>(&mut self, name: &str, handler: F) {
+	pub fn connect_mut<Args: 'static, F: Fn(&(*mut Self, Args)) -> () + 'static>(&mut self, name: &str, handler: F) -> Connection {
 		let signal = self.signals.get_mut(&name.to_string())
 			.expect(format!("No such signal: {}", name).as_str());
 
 		let handler = Box::new(Box::new(handler) as Box<dyn Fn(&(*mut Self, Args))>) as Box<dyn Any>;
-		signal.connect_any(handler);
-	}
-
-	pub fn disconnect<Args: 'static, F: Fn(&(*const Self, Args)) -> () + 'static>(&mut self, name: &str, handler: F) {
-		let signal = self.signals.get_mut(&name.to_string())
-			.expect(format!("No such signal: {}", name).as_str());
-
-		let handler = Box::new(Box::new(handler) as Box<dyn Fn(&(*const Self, Args))>) as Box<dyn Any>;
-		signal.disconnect_any(handler);
+		signal.connect_any(handler)
 	}
 
-	pub fn disconnect_mut<Args: 'static, F: Fn(&(*mut Self, Args)) -> () + 'static>(&mut self, name: &str, handler: F) {
+	pub fn disconnect(&mut self, name: &str, id: impl Into<ConnectionId>) -> bool {
 		let signal = self.signals.get_mut(&name.to_string())
 			.expect(format!("No such signal: {}", name).as_str());
 
-		let handler = Box::new(Box::new(handler) as Box<dyn Fn(&(*mut Self, Args))>) as Box<dyn Any>;
-		signal.disconnect_any(handler);
+		signal.disconnect_any(id.into())
 	}
 
 	pub fn emit<Args: 'static>(&self, name: &str, args: Args) {
@@ -199,6 +391,158 @@ impl Node {
 		}
 	}
 
+	/// Queues the emission on the `SignalQueue`. When it's flushed, the node is looked up again by its id,
+	/// so handlers are free to lock and modify the scene. Nothing is emitted if the node was removed in
+	/// the meantime. Nodes that aren't in a scene have nothing to be looked up in, so they emit immediately.
+	/// Like `StaticSignal::emit_deferred`, this only defers on the thread that flushes the queue.
+	pub fn emit_deferred<Args: 'static>(&self, name: &str, args: Args) {
+		let Some(scene) = &self.scene else {
+			log::warn!("{:?} is not in a scene, emitting {} immediately", self, name);
+			self.emit(name, args);
+			return;
+		};
+
+		let scene = Arc::downgrade(scene);
+		let id = self.id;
+		let name = name.to_string();
+
+		SignalQueue::push(move || {
+			let Some(scene) = scene.upgrade() else { return; };
+
+			let emission = {
+				let scene = scene.read().unwrap();
+
+				scene.node(id).and_then(|node| {
+					let signal = node.signals.get(&name)?;
+					Some((node as *const Self, signal.snapshot_any()))
+				})
+			};
+
+			if let Some((node, snapshot)) = emission {
+				let snapshot = snapshot.downcast::<SignalSnapshot<(*const Self, Args)>>()
+					.expect("Signal called with invalid arguments");
+
+				snapshot.emit((node, args));
+			}
+		});
+	}
+
+	/// Same as `emit_deferred`, for signals created with `create_signal_mut`.
+	pub fn emit_mut_deferred<Args: 'static>(&mut self, name: &str, args: Args) {
+		let Some(scene) = &self.scene else {
+			log::warn!("{:?} is not in a scene, emitting {} immediately", self, name);
+			self.emit_mut(name, args);
+			return;
+		};
+
+		let scene = Arc::downgrade(scene);
+		let id = self.id;
+		let name = name.to_string();
+
+		SignalQueue::push(move || {
+			let Some(scene) = scene.upgrade() else { return; };
+
+			let emission = {
+				let mut scene = scene.write().unwrap();
+
+				scene.node_mut(id).and_then(|node| {
+					let snapshot = node.signals.get(&name)?.snapshot_any();
+					Some((node as *mut Self, snapshot))
+				})
+			};
+
+			if let Some((node, snapshot)) = emission {
+				let snapshot = snapshot.downcast::<SignalSnapshot<(*mut Self, Args)>>()
+					.expect("Signal called with invalid arguments");
+
+				snapshot.emit((node, args));
+			}
+		});
+	}
+
+	// typed keys
+	// These panic if the node has the signal with other arguments than the key's, e.g. because it was created
+	// through another key with the same name.
+
+	/// Connects to the signal named by `key`, creating it if the node doesn't have it yet.
+	pub fn connect_key<Args: 'static, F: Fn(&(*const Self, Args)) + 'static>(&mut self, key: NodeSignalKey<Args>, handler: F) -> Connection {
+		self.key_signal(key);
+		self.connect(key.name(), handler)
+	}
+
+	pub fn connect_mut_key<Args: 'static, F: Fn(&(*mut Self, Args)) + 'static>(&mut self, key: NodeSignalKeyMut<Args>, handler: F) -> Connection {
+		self.key_signal(key);
+		self.connect_mut(key.name(), handler)
+	}
+
+	/// Emits the signal named by `key`. A signal nobody has connected to yet has no handlers to call,
+	/// so that's not an error.
+	pub fn emit_key<Args: 'static>(&self, key: NodeSignalKey<Args>, args: Args) {
+		if let Some(signal) = self.existing_key_signal(key) {
+			signal.emit_any(&(self as *const Self, args));
+		}
+	}
+
+	pub fn emit_mut_key<Args: 'static>(&mut self, key: NodeSignalKeyMut<Args>, args: Args) {
+		let node = self as *mut Self;
+
+		if let Some(signal) = self.existing_key_signal(key) {
+			signal.emit_any(&(node, args));
+		}
+	}
+
+	pub fn emit_key_deferred<Args: 'static>(&self, key: NodeSignalKey<Args>, args: Args) {
+		if self.existing_key_signal(key).is_some() {
+			self.emit_deferred(key.name(), args);
+		}
+	}
+
+	pub fn emit_mut_key_deferred<Args: 'static>(&mut self, key: NodeSignalKeyMut<Args>, args: Args) {
+		if self.existing_key_signal(key).is_some() {
+			self.emit_mut_deferred(key.name(), args);
+		}
+	}
+
+	/// Resolves with the arguments of the next emission of the signal named by `key`.
+	pub fn next_key<Args: Clone + 'static>(&mut self, key: NodeSignalKey<Args>) -> SignalFuture<(*const Self, Args)> {
+		let signal = self.key_signal(key);
+		SignalFuture::new(|handler| signal.connect_any_with(ConnectOptions::once(), Box::new(handler)))
+	}
+
+	/// A `SharedSignal` re-emitting the signal named by `key`, to be used with the `SignalSource` combinators.
+	/// The forwarding handler disconnects itself once the returned signal is dropped.
+	pub fn forward_key<Args: Clone + 'static>(&mut self, key: NodeSignalKey<Args>) -> SharedSignal<(*const Self, Args)> {
+		let forwarded = SharedSignal::new();
+		let target = forwarded.downgrade();
+
+		let connection: Rc<std::cell::OnceCell<Connection>> = Rc::default();
+		let this = connection.clone();
+
+		let handler_connection = self.connect_key(key, move |args| {
+			match target.upgrade() {
+				Some(forwarded) => { forwarded.emit(args.clone()); },
+				None => if let Some(connection) = this.get() { connection.disconnect(); }
+			}
+		});
+
+		let _ = connection.set(handler_connection);
+		forwarded
+	}
+
+	fn key_signal<Args: 'static>(&mut self, key: SignalKey<Args>) -> &mut Box<dyn Signal> {
+		let signal = self.signals.entry(key.name().to_string())
+			.or_insert_with(|| Box::new(StaticSignal::<Args>::with_name(key.name())));
+
+		signal.expect_args::<Args>(key.name());
+		signal
+	}
+
+	fn existing_key_signal<Args: 'static>(&self, key: SignalKey<Args>) -> Option<&dyn Signal> {
+		let signal = self.signals.get(key.name())?.as_ref();
+		signal.expect_args::<Args>(key.name());
+		Some(signal)
+	}
+
 	// pub fn emit_strict<Args: 'static>(&self, name: &str, args: Args) {
 	// 	let args = (self as *const Self, args);
 
@@ -0,0 +1,137 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{NodeId, SceneGraph};
+
+#[derive(Default)]
+pub(crate) struct NameIndex {
+	by_name: HashMap<String, Vec<NodeId>>,
+	names: HashMap<NodeId, String>
+}
+
+impl NameIndex {
+	pub(crate) fn insert(&mut self, id: NodeId, name: &str) {
+		self.by_name.entry(name.to_string()).or_default().push(id);
+		self.names.insert(id, name.to_string());
+	}
+
+	pub(crate) fn remove(&mut self, id: NodeId) {
+		let Some(name) = self.names.remove(&id) else { return; };
+
+		if let Some(ids) = self.by_name.get_mut(&name) {
+			ids.retain(|other| *other != id);
+
+			if ids.is_empty() {
+				self.by_name.remove(&name);
+			}
+		}
+	}
+
+	pub(crate) fn get(&self, name: &str) -> &[NodeId] {
+		self.by_name.get(name).map_or(&[], |ids| ids.as_slice())
+	}
+}
+
+/// Whether `name` matches a path segment, where `*` matches any run of characters and `?` any single one.
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+	let pattern: Vec<char> = pattern.chars().collect();
+	let name: Vec<char> = name.chars().collect();
+
+	// where to continue after the last `*` if the rest doesn't match
+	let (mut p, mut n) = (0, 0);
+	let mut backtrack: Option<(usize, usize)> = None;
+
+	while n < name.len() {
+		if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+			p += 1;
+			n += 1;
+		} else if p < pattern.len() && pattern[p] == '*' {
+			backtrack = Some((p, n));
+			p += 1;
+		} else if let Some((star, matched)) = backtrack {
+			p = star + 1;
+			n = matched + 1;
+			backtrack = Some((star, matched + 1));
+		} else {
+			return false;
+		}
+	}
+
+	pattern[p..].iter().all(|c| *c == '*')
+}
+
+impl SceneGraph {
+	/// Looks up a node by its path from the root, e.g. `"Level/Player/Camera"`. See `get_node_from`.
+	pub fn get_node(&self, path: &str) -> Option<NodeId> {
+		self.get_node_from(self.root_id(), path)
+	}
+
+	/// Looks up a node by its path from `from`. Segments are child names, `..` is the parent and `.` the node
+	/// itself, paths starting with `/` start at the root. Segments may contain `*` and `?` wildcards, the first
+	/// match is returned.
+	pub fn get_node_from(&self, from: NodeId, path: &str) -> Option<NodeId> {
+		self.get_nodes_from(from, path).into_iter().next()
+	}
+
+	/// Every node matching the path from the root, e.g. `"Enemies/*"`.
+	pub fn get_nodes(&self, path: &str) -> Vec<NodeId> {
+		self.get_nodes_from(self.root_id(), path)
+	}
+
+	/// Every node matching the path from `from`, in tree order. See `get_node_from`.
+	pub fn get_nodes_from(&self, from: NodeId, path: &str) -> Vec<NodeId> {
+		if !self.contains(from) {
+			return Vec::new();
+		}
+
+		let mut current = vec![if path.starts_with('/') { self.root_id() } else { from }];
+
+		for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+			let mut next = Vec::new();
+
+			for node in current {
+				match segment {
+					"." => next.push(node),
+					".." => if node != self.root_id() { next.push(self.parent(node)) },
+					_ if segment.contains(['*', '?']) => next.extend(self.children_slice(node).iter().copied().filter(|child| {
+						matches_pattern(segment, self.node(*child).unwrap().name())
+					})),
+					_ => {
+						// the index is in insertion order, siblings may have been moved since
+						let mut named: Vec<NodeId> = self.name_index().get(segment).iter().copied()
+							.filter(|id| *id != self.root_id() && self.parent(*id) == node)
+							.collect();
+
+						named.sort_by_key(|id| self.index_in_parent(*id));
+						next.extend(named);
+					}
+				}
+			}
+
+			// `..` from siblings leads to the same parent, and not necessarily next to each other
+			let mut seen = HashSet::new();
+			next.retain(|id| seen.insert(*id));
+
+			if next.is_empty() {
+				return next;
+			}
+
+			current = next;
+		}
+
+		current
+	}
+
+	/// The absolute path of the node, without the root.
+	pub fn path_of(&self, id: NodeId) -> Option<String> {
+		let mut names = Vec::new();
+		let mut current = id;
+
+		while current != self.root_id() {
+			names.push(self.node(current)?.name());
+			current = self.parent(current);
+		}
+
+		names.reverse();
+		Some(format!("/{}", names.join("/")))
+	}
+}
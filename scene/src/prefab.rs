@@ -0,0 +1,328 @@
+use std::{any::{Any, TypeId}, collections::HashMap, path::{Path, PathBuf}};
+
+use crate::{ComponentRegistry, NodeComponent, NodeId, NodeTree, NodeTreeEntry, SceneGraph, SharedSceneGraph, SnapshotMode, error::{ErrorKind, SceneError}, reflect::Value, tree};
+
+/// A change an instance makes to its prefab. `node` is the path of the changed node from the instance root,
+/// `"."` for the root itself, see `SceneGraph::get_node_from`.
+#[derive(Clone)]
+pub enum PrefabOverride {
+	/// Sets a field of a reflected component.
+	Field { node: String, component: TypeId, field: String, value: Value },
+	/// Replaces the node's component of the same type, or adds it. Made with `PrefabOverride::component`.
+	Component { node: String, component: OverrideComponent }
+}
+
+/// The component of a `PrefabOverride::Component`, one that can be cloned, since every instance gets its own copy.
+pub struct OverrideComponent(Box<dyn NodeComponent>);
+
+impl OverrideComponent {
+	pub fn get(&self) -> &dyn NodeComponent { self.0.as_ref() }
+
+	fn instance(&self) -> Box<dyn NodeComponent> {
+		self.0.clone_component().unwrap()
+	}
+}
+
+impl Clone for OverrideComponent {
+	fn clone(&self) -> Self {
+		Self(self.instance())
+	}
+}
+
+impl PrefabOverride {
+	pub fn field<T: NodeComponent>(node: &str, field: &str, value: Value) -> Self {
+		Self::Field { node: node.to_string(), component: TypeId::of::<T>(), field: field.to_string(), value }
+	}
+
+	/// Fails if the component can't be cloned, since every instance gets its own copy.
+	pub fn component(node: &str, component: Box<dyn NodeComponent>) -> Option<Self> {
+		component.clone_component()?;
+		Some(Self::Component { node: node.to_string(), component: OverrideComponent(component) })
+	}
+
+	pub fn node(&self) -> &str {
+		match self {
+			Self::Field { node, .. } | Self::Component { node, .. } => node
+		}
+	}
+
+	fn apply(&self, scene: &mut SceneGraph, root: NodeId) {
+		let Some(id) = scene.get_node_from(root, self.node()) else {
+			log::warn!("Prefab instance {} has no node at {}, ignoring its override", root, self.node());
+			return;
+		};
+
+		let node = scene.node_mut(id).unwrap();
+
+		match self {
+			Self::Field { component, field, value, .. } => {
+				let Some(reflect) = node.component_of_type_mut(*component).and_then(|component| component.reflect_mut()) else {
+					log::warn!("{:?} has no reflected component to override {} of", node, field);
+					return;
+				};
+
+				if let Err(e) = reflect.set_field(field, value.clone()) {
+					log::warn!("Failed to override {} of {:?}: {}", field, node, e);
+				}
+			},
+			Self::Component { component, .. } => {
+				node.remove_component_of_type(Any::type_id(component.get().as_any()));
+				node.add_component(component.instance());
+			}
+		}
+	}
+}
+
+/// What a prefab instance is made from: the prefab's path in its `PrefabLibrary` and what the instance changes.
+#[derive(Clone)]
+pub struct PrefabLink {
+	pub path: PathBuf,
+	pub overrides: Vec<PrefabOverride>
+}
+
+impl PrefabLink {
+	pub fn new<P: AsRef<Path>>(path: P) -> Self {
+		Self {
+			path: path.as_ref().to_path_buf(),
+			overrides: Vec::new()
+		}
+	}
+
+	pub fn with_override(mut self, r#override: PrefabOverride) -> Self {
+		self.overrides.push(r#override);
+		self
+	}
+
+	pub(crate) fn apply(&self, scene: &mut SceneGraph, root: NodeId) {
+		for r#override in &self.overrides {
+			r#override.apply(scene, root);
+		}
+	}
+}
+
+/// Marks the root node of a prefab instance. Added when the instance is created, so it can be rebuilt and
+/// saved as a link to its prefab.
+#[derive(Clone)]
+pub struct PrefabInstance {
+	owner: NodeId,
+	scene: Option<SharedSceneGraph>,
+
+	link: PrefabLink
+}
+
+impl PrefabInstance {
+	pub fn new(link: PrefabLink) -> Self {
+		Self { owner: NodeId::INVALID, scene: None, link }
+	}
+
+	pub fn link(&self) -> &PrefabLink { &self.link }
+	pub fn prefab(&self) -> &Path { &self.link.path }
+}
+
+impl NodeComponent for PrefabInstance {
+	fn name(&self) -> &str { "PrefabInstance" }
+
+	fn enter_scene(&mut self, owner: NodeId, scene: SharedSceneGraph) {
+		self.owner = owner;
+		self.scene = Some(scene);
+	}
+
+	fn exit_scene(&mut self) {
+		self.owner = NodeId::INVALID;
+		self.scene = None;
+	}
+
+	fn clone_component(&self) -> Option<Box<dyn NodeComponent>> { Some(Box::new(self.clone())) }
+
+	// part of the scene's structure, not its state
+	fn snapshot_mode(&self) -> SnapshotMode { SnapshotMode::Skip }
+
+	fn as_any(&self) -> &dyn Any { self }
+	fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
+
+/// Prefab files, loaded from a directory with a `ComponentRegistry` and kept around. Prefabs are `NodeTree`s whose
+/// entries may link to other prefabs, which the library fills in before they're instantiated.
+pub struct PrefabLibrary {
+	directory: PathBuf,
+	registry: ComponentRegistry,
+
+	// as they were loaded, links not filled in
+	prefabs: HashMap<PathBuf, NodeTree>
+}
+
+impl PrefabLibrary {
+	pub fn new<P: AsRef<Path>>(directory: P, registry: ComponentRegistry) -> Self {
+		Self {
+			directory: directory.as_ref().to_path_buf(),
+			registry,
+			prefabs: HashMap::new()
+		}
+	}
+
+	pub fn directory(&self) -> &Path { &self.directory }
+	pub fn registry(&self) -> &ComponentRegistry { &self.registry }
+
+	/// Adds a prefab that doesn't come from a file, or replaces a loaded one.
+	pub fn insert<P: AsRef<Path>>(&mut self, path: P, tree: NodeTree) {
+		self.prefabs.insert(path.as_ref().to_path_buf(), tree);
+	}
+
+	/// Loads the prefab file again, e.g. after it was changed. Existing instances stay as they are until `refresh`.
+	pub fn reload<P: AsRef<Path>>(&mut self, path: P) -> Result<(), SceneError> {
+		let tree = NodeTree::load(self.directory.join(&path), &self.registry)?;
+		self.prefabs.insert(path.as_ref().to_path_buf(), tree);
+		Ok(())
+	}
+
+	/// The prefab with everything it links to filled in, loaded if it isn't yet. Its root links back to it.
+	pub fn get<P: AsRef<Path>>(&mut self, path: P) -> Result<NodeTree, SceneError> {
+		let mut tree = NodeTree { root: NodeTreeEntry::instance(path) };
+		self.expand(&mut tree)?;
+		Ok(tree)
+	}
+
+	/// Fills in the entries of the tree that link to prefabs, e.g. a scene loaded with `NodeTree::load`.
+	pub fn expand(&mut self, tree: &mut NodeTree) -> Result<(), SceneError> {
+		self.expand_entry(&mut tree.root, &mut Vec::new())
+	}
+
+	/// Adds an instance of the prefab to the scene, see `NodeTree::instantiate`.
+	pub fn instantiate<P: AsRef<Path>>(
+		&mut self,
+		path: P,
+		overrides: Vec<PrefabOverride>,
+		scene: &SharedSceneGraph,
+		parent: Option<NodeId>
+	) -> Result<NodeId, SceneError> {
+		let mut tree = self.get(path)?;
+		tree.root.prefab.as_mut().unwrap().overrides = overrides;
+
+		Ok(tree.instantiate(scene.clone(), parent))
+	}
+
+	/// Loads a scene file from the directory, prefab instances in it included.
+	pub fn load_scene<P: AsRef<Path>>(&mut self, path: P) -> Result<SharedSceneGraph, SceneError> {
+		let mut tree = NodeTree::load(self.directory.join(path), &self.registry)?;
+		self.expand(&mut tree)?;
+
+		Ok(SceneGraph::from_tree(&tree))
+	}
+
+	/// Rebuilds the instances of the prefab in the scene, along with instances of prefabs containing it, e.g. after
+	/// `reload`. Instance roots keep their ids, name and groups, everything else is recreated. Returns how many
+	/// instances were rebuilt.
+	pub fn refresh<P: AsRef<Path>>(&mut self, scene: &SharedSceneGraph, path: P) -> Result<usize, SceneError> {
+		let instances: Vec<(NodeId, PrefabLink)> = scene.read().unwrap().query::<PrefabInstance>()
+			.map(|(id, instance)| (id, instance.link.clone()))
+			.collect();
+
+		let mut affected = Vec::new();
+
+		for (id, link) in instances {
+			if self.depends_on(&link.path, path.as_ref(), &mut Vec::new())? {
+				affected.push((id, link));
+			}
+		}
+
+		// instances inside other affected instances are rebuilt along with them
+		let outermost: Vec<(NodeId, PrefabLink)> = {
+			let graph = scene.read().unwrap();
+
+			affected.iter()
+				.filter(|(id, _)| !affected.iter().any(|(other, _)| other != id && graph.is_ancestor(*other, *id)))
+				.cloned()
+				.collect()
+		};
+
+		for (id, link) in &outermost {
+			let mut entry = NodeTreeEntry::new();
+			entry.prefab = Some(link.clone());
+			self.expand_entry(&mut entry, &mut Vec::new())?;
+
+			tree::rebuild_entry(&mut scene.write().unwrap(), &entry, *id);
+		}
+
+		Ok(outermost.len())
+	}
+
+	fn load(&mut self, path: &Path) -> Result<&NodeTree, SceneError> {
+		if !self.prefabs.contains_key(path) {
+			self.reload(path)?;
+		}
+
+		Ok(&self.prefabs[path])
+	}
+
+	/// `stack` holds the prefabs being filled in, to catch prefabs that end up containing themselves.
+	fn expand_entry(&mut self, entry: &mut NodeTreeEntry, stack: &mut Vec<PathBuf>) -> Result<(), SceneError> {
+		let Some(path) = entry.prefab.as_ref().map(|link| link.path.clone()) else {
+			for child in &mut entry.children {
+				self.expand_entry(child, stack)?;
+			}
+
+			return Ok(());
+		};
+
+		if stack.contains(&path) {
+			return Err(SceneError::new(ErrorKind::Other, &format!("Prefab {} contains itself", path.display())));
+		}
+
+		let mut root = self.load(&path)?.root.clone();
+
+		if root.prefab.take().is_some() {
+			log::warn!("The root of prefab {} links to another prefab, which isn't supported", path.display());
+		}
+
+		stack.push(path);
+		let expanded = self.expand_entry(&mut root, stack);
+		stack.pop();
+		expanded?;
+
+		if entry.name.is_none() {
+			entry.name = root.name;
+		}
+
+		if entry.process.is_default() {
+			entry.process = root.process;
+		}
+
+		for group in root.groups {
+			if !entry.groups.contains(&group) {
+				entry.groups.push(group);
+			}
+		}
+
+		entry.components = root.components;
+		entry.children = root.children;
+		Ok(())
+	}
+
+	fn depends_on(&mut self, prefab: &Path, path: &Path, stack: &mut Vec<PathBuf>) -> Result<bool, SceneError> {
+		if prefab == path {
+			return Ok(true);
+		}
+
+		if stack.iter().any(|other| other == prefab) {
+			return Ok(false);
+		}
+
+		let mut links = Vec::new();
+		self.load(prefab)?.root.visit(&mut |entry| {
+			if let Some(link) = &entry.prefab {
+				links.push(link.path.clone());
+			}
+		});
+
+		stack.push(prefab.to_path_buf());
+
+		for link in links {
+			if self.depends_on(&link, path, stack)? {
+				return Ok(true);
+			}
+		}
+
+		stack.pop();
+		Ok(false)
+	}
+}
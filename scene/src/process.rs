@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{NodeId, SceneGraph};
+
+/// When a node updates, depending on whether the scene is paused. See `SceneGraph::process_order`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ProcessMode {
+	/// Same as the parent, `Pausable` for the root.
+	#[default]
+	Inherit,
+	/// Only while not paused.
+	Pausable,
+	/// Only while paused, e.g. a pause menu.
+	WhenPaused,
+	Always
+}
+
+impl ProcessMode {
+	pub fn can_process(&self, paused: bool) -> bool {
+		match self {
+			Self::Inherit | Self::Pausable => !paused,
+			Self::WhenPaused => paused,
+			Self::Always => true
+		}
+	}
+}
+
+/// How a node takes part in updates, see `Node::set_enabled`, `Node::set_process_mode` and
+/// `Node::set_process_priority`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProcessSettings {
+	pub enabled: bool,
+	pub mode: ProcessMode,
+	pub priority: i32
+}
+
+impl ProcessSettings {
+	pub fn is_default(&self) -> bool {
+		*self == Self::default()
+	}
+}
+
+impl Default for ProcessSettings {
+	fn default() -> Self {
+		Self {
+			enabled: true,
+			mode: ProcessMode::Inherit,
+			priority: 0
+		}
+	}
+}
+
+impl SceneGraph {
+	/// Whether the node and all of its parents are enabled.
+	pub fn is_enabled_in_tree(&self, id: NodeId) -> bool {
+		self.ancestors_and_self(id).all(|id| self.node(id).is_some_and(|node| node.is_enabled()))
+	}
+
+	/// The node's process mode, or the first one above it that isn't `Inherit`.
+	pub fn resolved_process_mode(&self, id: NodeId) -> ProcessMode {
+		self.ancestors_and_self(id)
+			.filter_map(|id| self.node(id))
+			.map(|node| node.process_mode())
+			.find(|mode| *mode != ProcessMode::Inherit)
+			.unwrap_or(ProcessMode::Pausable)
+	}
+
+	/// Whether the node updates this frame.
+	pub fn can_process(&self, id: NodeId, paused: bool) -> bool {
+		self.is_enabled_in_tree(id) && self.resolved_process_mode(id).can_process(paused)
+	}
+
+	/// The nodes that update this frame, the root left out. They're ordered by process priority, lowest first,
+	/// and in depth-first order within the same priority. Disabled nodes are skipped along with everything below them.
+	pub fn process_order(&self, paused: bool) -> Vec<NodeId> {
+		let mut order = Vec::new();
+
+		if !self.root().is_enabled() {
+			return order;
+		}
+
+		let root_mode = self.resolved_process_mode(self.root_id());
+		let mut stack: Vec<(NodeId, ProcessMode)> = self.children_slice(self.root_id()).iter().rev()
+			.map(|child| (*child, root_mode))
+			.collect();
+
+		while let Some((id, inherited)) = stack.pop() {
+			let Some(node) = self.node(id) else { continue; };
+
+			if !node.is_enabled() {
+				continue;
+			}
+
+			let mode = match node.process_mode() {
+				ProcessMode::Inherit => inherited,
+				mode => mode
+			};
+
+			if mode.can_process(paused) {
+				order.push(id);
+			}
+
+			for child in self.children_slice(id).iter().rev() {
+				stack.push((*child, mode));
+			}
+		}
+
+		// stable, so depth-first order holds between equal priorities
+		order.sort_by_key(|id| self.node(*id).unwrap().process_priority());
+		order
+	}
+
+	fn ancestors_and_self(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+		std::iter::successors(self.contains(id).then_some(id), move |id| {
+			(*id != self.root_id()).then(|| self.parent(*id))
+		})
+	}
+}
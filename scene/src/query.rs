@@ -0,0 +1,77 @@
+use std::any::TypeId;
+
+use crate::{Node, NodeComponent, NodeId, SceneGraph};
+
+/// What `SceneGraph::query` looks for, a component type or a tuple of up to four different ones.
+pub trait ComponentQuery {
+	type Item<'a>;
+	type ItemMut<'a>;
+
+	fn type_ids() -> Vec<TypeId>;
+
+	fn fetch(node: &Node) -> Option<Self::Item<'_>>;
+	fn fetch_mut(node: &mut Node) -> Option<Self::ItemMut<'_>>;
+}
+
+impl<T: NodeComponent> ComponentQuery for T {
+	type Item<'a> = &'a T;
+	type ItemMut<'a> = &'a mut T;
+
+	fn type_ids() -> Vec<TypeId> { vec![TypeId::of::<T>()] }
+
+	fn fetch(node: &Node) -> Option<&T> { node.component::<T>() }
+	fn fetch_mut(node: &mut Node) -> Option<&mut T> { node.component_mut::<T>() }
+}
+
+macro_rules! component_query {
+	($($ty: ident $var: ident),+) => {
+		impl<$($ty: NodeComponent),+> ComponentQuery for ($($ty,)+) {
+			type Item<'a> = ($(&'a $ty,)+);
+			type ItemMut<'a> = ($(&'a mut $ty,)+);
+
+			fn type_ids() -> Vec<TypeId> { vec![$(TypeId::of::<$ty>()),+] }
+
+			fn fetch(node: &Node) -> Option<Self::Item<'_>> {
+				Some(($(node.component::<$ty>()?,)+))
+			}
+
+			fn fetch_mut(node: &mut Node) -> Option<Self::ItemMut<'_>> {
+				let [$($var),+] = node.components_disjoint_mut([$(TypeId::of::<$ty>()),+])?;
+				Some(($($var.as_any_mut().downcast_mut::<$ty>()?,)+))
+			}
+		}
+	};
+}
+
+component_query!(A a);
+component_query!(A a, B b);
+component_query!(A a, B b, C c);
+component_query!(A a, B b, C c, D d);
+
+impl SceneGraph {
+	/// Every node with all the components in `Q`, along with them, e.g. `scene.query::<(Transform3D, Model)>()`.
+	/// Only nodes with the rarest of the component types are looked at, in no particular order.
+	pub fn query<Q: ComponentQuery>(&self) -> impl Iterator<Item = (NodeId, Q::Item<'_>)> {
+		self.query_candidates::<Q>().into_iter()
+			.filter_map(|id| Some((id, Q::fetch(self.node(id)?)?)))
+	}
+
+	/// Calls `f` with every node with all the components in `Q`, along with them. See `query`.
+	pub fn query_mut<Q: ComponentQuery, F: FnMut(NodeId, Q::ItemMut<'_>)>(&mut self, mut f: F) {
+		for id in self.query_candidates::<Q>() {
+			if let Some(components) = self.node_mut(id).and_then(|node| Q::fetch_mut(node)) {
+				f(id, components);
+			}
+		}
+	}
+
+	/// Nodes with the rarest of the component types, which any match has to be among.
+	fn query_candidates<Q: ComponentQuery>(&self) -> Vec<NodeId> {
+		let index = self.component_index();
+
+		Q::type_ids().iter()
+			.map(|type_id| index.get(type_id))
+			.min_by_key(|ids| ids.len())
+			.map_or_else(Vec::new, |ids| ids.to_vec())
+	}
+}
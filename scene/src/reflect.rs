@@ -0,0 +1,142 @@
+//! Generic access to component fields, for inspectors and other tooling. `#[derive(Reflect)]` implements
+//! `Reflect` for every field except `owner` and `scene`, field types have to implement `ReflectValue`.
+//!
+//! Field attributes:
+//! - `#[reflect(skip)]` leaves the field out
+//! - `#[reflect(read_only)]` rejects writes
+//! - `#[reflect(set = "method")]` writes through a setter instead of the field, e.g. one that marks the component dirty
+//! - `#[reflect(with = "module")]` converts with `module::KIND`, `module::to_value` and `module::from_value` (not needed
+//!   when `read_only`), for types that can't implement `ReflectValue` here
+
+use std::{fmt, path::PathBuf};
+
+use glam::{Quat, Vec2, Vec3, Vec4};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Value {
+	Bool(bool),
+	I32(i32),
+	U32(u32),
+	F32(f32),
+	String(String),
+	Vec2(Vec2),
+	Vec3(Vec3),
+	Vec4(Vec4),
+	Quat(Quat),
+	/// RGBA
+	Color(Vec4),
+	/// Path of a resource.
+	Resource(PathBuf)
+}
+
+impl Value {
+	pub fn kind(&self) -> ValueKind {
+		match self {
+			Self::Bool(_) => ValueKind::Bool,
+			Self::I32(_) => ValueKind::I32,
+			Self::U32(_) => ValueKind::U32,
+			Self::F32(_) => ValueKind::F32,
+			Self::String(_) => ValueKind::String,
+			Self::Vec2(_) => ValueKind::Vec2,
+			Self::Vec3(_) => ValueKind::Vec3,
+			Self::Vec4(_) => ValueKind::Vec4,
+			Self::Quat(_) => ValueKind::Quat,
+			Self::Color(_) => ValueKind::Color,
+			Self::Resource(_) => ValueKind::Resource
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueKind {
+	Bool,
+	I32,
+	U32,
+	F32,
+	String,
+	Vec2,
+	Vec3,
+	Vec4,
+	Quat,
+	Color,
+	Resource
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldInfo {
+	pub name: &'static str,
+	pub kind: ValueKind,
+	pub read_only: bool
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReflectError {
+	NoSuchField(String),
+	ReadOnly(&'static str),
+	TypeMismatch { field: &'static str, expected: ValueKind, found: ValueKind }
+}
+
+impl fmt::Display for ReflectError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::NoSuchField(field) => write!(f, "No such field: {}", field),
+			Self::ReadOnly(field) => write!(f, "{} is read-only", field),
+			Self::TypeMismatch { field, expected, found } => write!(f, "{} is a {:?}, got a {:?}", field, expected, found)
+		}
+	}
+}
+
+impl std::error::Error for ReflectError {}
+
+pub trait Reflect {
+	fn fields(&self) -> &'static [FieldInfo];
+
+	fn field(&self, name: &str) -> Option<Value>;
+	fn set_field(&mut self, name: &str, value: Value) -> Result<(), ReflectError>;
+
+	fn field_info(&self, name: &str) -> Option<&'static FieldInfo> {
+		self.fields().iter().find(|field| field.name == name)
+	}
+}
+
+/// A type component fields can have to be reflected.
+pub trait ReflectValue: Sized {
+	const KIND: ValueKind;
+
+	fn to_value(&self) -> Value;
+	fn from_value(value: Value) -> Option<Self>;
+}
+
+macro_rules! reflect_value {
+	($ty: ty, $variant: ident) => {
+		impl ReflectValue for $ty {
+			const KIND: ValueKind = ValueKind::$variant;
+
+			fn to_value(&self) -> Value { Value::$variant(self.clone()) }
+
+			fn from_value(value: Value) -> Option<Self> {
+				match value {
+					Value::$variant(value) => Some(value),
+					_ => None
+				}
+			}
+		}
+	};
+}
+
+reflect_value!(bool, Bool);
+reflect_value!(i32, I32);
+reflect_value!(u32, U32);
+reflect_value!(f32, F32);
+reflect_value!(String, String);
+reflect_value!(Vec2, Vec2);
+reflect_value!(Vec3, Vec3);
+reflect_value!(Vec4, Vec4);
+reflect_value!(Quat, Quat);
+reflect_value!(PathBuf, Resource);
+
+/// Used by `#[derive(Reflect)]` for values of the wrong kind.
+pub fn type_mismatch(field: &'static str, expected: ValueKind, value: &Value) -> ReflectError {
+	ReflectError::TypeMismatch { field, expected, found: value.kind() }
+}
@@ -1,11 +1,21 @@
-use std::{collections::{HashMap, VecDeque}, fmt::Debug, rc::Rc, sync::{Arc, Mutex, RwLock}, vec};
+use std::{any::TypeId, cell::{Ref, RefCell}, collections::{HashMap, VecDeque}, fmt::Debug, rc::Rc, sync::{Arc, Mutex, RwLock}, vec};
 
 use fatum_signals::StaticSignal;
 
-use crate::{Node, NodeComponent, NodeId, iterators::{SceneDfsIterator, ScenePostDfsIterator}};
+use crate::{Node, NodeComponent, NodeId, index::MemberIndex, path::NameIndex};
 
 pub type SharedSceneGraph = Arc<RwLock<SceneGraph>>;
 
+/// Nodes whose name, groups or components changed since the scene last updated its indices. Nodes push to it themselves,
+/// since they can't reach the scene while it's locked to get them.
+pub(crate) type IndexQueue = Arc<Mutex<Vec<NodeId>>>;
+
+/// Nodes to remove once it's safe to, see `SceneGraph::queue_remove`.
+pub(crate) type RemovalQueue = Arc<Mutex<Vec<NodeId>>>;
+
+/// A tree of nodes. Only ever created inside its `SharedSceneGraph`, by `new` and `from_tree`, and it must never be
+/// moved out of it, e.g. through `mem::swap` or `mem::replace` on a write guard: the nodes in it hold pointers back
+/// to it in their component signal handlers.
 pub struct SceneGraph {
 	this: Option<SharedSceneGraph>,
 
@@ -13,17 +23,37 @@ pub struct SceneGraph {
 	child_parent: HashMap<NodeId, NodeId>,
 	parent_children: HashMap<NodeId, Vec<NodeId>>,
 
+	// current generation of every slot, and the slots free to be reused
+	generations: Vec<u32>,
+	free: Vec<u32>,
+
+	names: RefCell<NameIndex>,
+	groups: RefCell<MemberIndex<String>>,
+	components: RefCell<MemberIndex<TypeId>>,
+	changed: IndexQueue,
+	removals: RemovalQueue,
+
 	root: NodeId,
 
 	pub node_added: StaticSignal<(*const Self, *const Node)>,
+	/// Emitted for each removed node, children before their parents, while it still has its id. Its children are
+	/// gone by then, but it's still in its parent.
 	pub node_removed: StaticSignal<(*const Self, *const Node)>,
+	/// Emitted when a node gets a new parent or a new place among its siblings, along with its previous parent and
+	/// its index there.
+	pub node_moved: StaticSignal<(*const Self, *const Node, NodeId, usize)>,
 	pub node_component_added: StaticSignal<(*const Node, *const Box<dyn NodeComponent>)>,
 	pub node_component_removed: StaticSignal<(*const Node, *const Box<dyn NodeComponent>)>,
+	/// Emitted with the group when a node in the scene joins it, nodes added to the scene join all of theirs.
+	/// Joining through `Node::add_to_group` is seen at the next `update_indices`.
+	pub node_added_to_group: StaticSignal<(*const Self, *const Node, String)>,
+	/// Emitted with the group when a node in the scene leaves it, removed nodes leave all of theirs.
+	pub node_removed_from_group: StaticSignal<(*const Self, *const Node, String)>,
 }
 
 impl SceneGraph {
 	pub fn new() -> SharedSceneGraph {
-		let root = Node::with_id_name(0, "SceneRoot");
+		let root = Node::with_id_name(NodeId::default(), "SceneRoot");
 
 		let this = Arc::new(RwLock::new(Self {
 			this: None,
@@ -32,18 +62,37 @@ impl SceneGraph {
 			]),
 			child_parent: HashMap::new(),
 			parent_children: HashMap::new(),
-			root: 0,
+			generations: vec![0],
+			free: Vec::new(),
+			names: RefCell::default(),
+			groups: RefCell::default(),
+			components: RefCell::default(),
+			changed: IndexQueue::default(),
+			removals: RemovalQueue::default(),
+			root: NodeId::default(),
 			node_added: StaticSignal::new(),
 			node_removed: StaticSignal::new(),
+			node_moved: StaticSignal::new(),
 			node_component_added: StaticSignal::new(),
-			node_component_removed: StaticSignal::new()
+			node_component_removed: StaticSignal::new(),
+			node_added_to_group: StaticSignal::new(),
+			node_removed_from_group: StaticSignal::new()
 		}));
 
 		{
 			// TODO this is extremely concerning
 			let mut scene = this.write().unwrap();
 			scene.this = Some(this.clone());
-			scene.nodes.get_mut(&0).unwrap().enter_scene(0, this.clone());
+			let root = scene.root;
+			let changed = scene.changed.clone();
+			let removals = scene.removals.clone();
+
+			let node = scene.nodes.get_mut(&root).unwrap();
+			node.enter_scene(root, this.clone());
+			node.changed = Some(changed);
+			node.removals = Some(removals);
+
+			scene.names.borrow_mut().insert(root, "SceneRoot");
 		}
 
 		this
@@ -53,6 +102,15 @@ impl SceneGraph {
 		self.nodes.get(&self.root).unwrap()
 	}
 
+	pub fn root_id(&self) -> NodeId { self.root }
+
+	/// Whether `id` belongs to a node that's still in the scene.
+	pub fn contains(&self, id: NodeId) -> bool {
+		self.nodes.contains_key(&id)
+	}
+
+	pub fn node_count(&self) -> usize { self.nodes.len() }
+
 	pub fn node(&self, id: NodeId) -> Option<&Node> {
 		self.nodes.get(&id)
 	}
@@ -62,23 +120,75 @@ impl SceneGraph {
 	}
 
 	pub fn node_by_name(&self, name: &str) -> Option<&Node> {
-		for (_, node) in &self.nodes {
-			if node.name() == name {
-				return Some(node);
-			}
-		}
-
-		None
+		let id = *self.name_index().get(name).first()?;
+		self.nodes.get(&id)
 	}
 
 	pub fn node_by_name_mut(&mut self, name: &str) -> Option<&mut Node> {
-		for (_, node) in &mut self.nodes {
-			if node.name() == name {
-				return Some(node);
+		let id = *self.name_index().get(name).first()?;
+		self.nodes.get_mut(&id)
+	}
+
+	/// Every node with this name, in the order they were added.
+	pub fn nodes_by_name(&self, name: &str) -> Vec<NodeId> {
+		self.name_index().get(name).to_vec()
+	}
+
+	pub(crate) fn name_index(&self) -> Ref<'_, NameIndex> {
+		self.update_indices();
+		self.names.borrow()
+	}
+
+	pub(crate) fn group_index(&self) -> Ref<'_, MemberIndex<String>> {
+		self.update_indices();
+		self.groups.borrow()
+	}
+
+	pub(crate) fn component_index(&self) -> Ref<'_, MemberIndex<TypeId>> {
+		self.update_indices();
+		self.components.borrow()
+	}
+
+	/// Catches the indices up with the nodes that changed, emitting the group signals for the groups they joined or
+	/// left through `Node`. Queries do this on their own, `SceneEngine` does it once a frame after processing the
+	/// scene.
+	pub fn update_indices(&self) {
+		let changed: Vec<NodeId> = self.changed.lock().unwrap().drain(..).collect();
+
+		if changed.is_empty() {
+			return;
+		}
+
+		let mut joined = Vec::new();
+		let mut left = Vec::new();
+
+		{
+			let mut names = self.names.borrow_mut();
+			let mut groups = self.groups.borrow_mut();
+			let mut components = self.components.borrow_mut();
+
+			for id in changed {
+				let Some(node) = self.nodes.get(&id) else { continue; };
+
+				names.remove(id);
+				names.insert(id, node.name());
+
+				let (added, removed) = groups.set(id, node.groups());
+				joined.extend(added.into_iter().map(|group| (id, group)));
+				left.extend(removed.into_iter().map(|group| (id, group)));
+
+				components.set(id, &node.component_types());
 			}
 		}
 
-		None
+		// handlers may query the indices again
+		for (id, group) in left {
+			self.node_removed_from_group.emit((self, &self.nodes[&id], group));
+		}
+
+		for (id, group) in joined {
+			self.node_added_to_group.emit((self, &self.nodes[&id], group));
+		}
 	}
 
 	pub fn parent(&self, child: NodeId) -> NodeId {
@@ -112,61 +222,297 @@ impl SceneGraph {
 		None
 	}
 
-	pub fn add_node(&mut self, mut node: Node, parent: Option<NodeId>) -> NodeId {
-		let new_id = self.nodes.len() as NodeId;
-		let parent = parent.unwrap_or_default(); // default == 0 == root
-		
-		if let Some(children) = self.parent_children.get_mut(&parent) {
-			children.push(new_id);
-		} else {
-			self.parent_children.insert(parent, vec![new_id]);
+	/// Every node below `id`, parents before their children and siblings in order. Unlike `SceneDfsIterator`,
+	/// this doesn't lock the scene, so it can be used while it's borrowed.
+	pub fn descendants(&self, id: NodeId) -> Vec<NodeId> {
+		let mut order = Vec::new();
+		let mut stack: Vec<NodeId> = self.children_slice(id).iter().rev().copied().collect();
+
+		while let Some(node) = stack.pop() {
+			order.push(node);
+			stack.extend(self.children_slice(node).iter().rev());
 		}
 
-		self.child_parent.insert(new_id, parent);
+		order
+	}
 
-		node.enter_scene(new_id, self.this.as_ref().unwrap().clone());
+	/// Where the node is among its siblings.
+	pub fn index_in_parent(&self, id: NodeId) -> Option<usize> {
+		let parent = self.child_parent.get(&id)?;
+		self.children_slice(*parent).iter().position(|child| *child == id)
+	}
 
-		node.component_added.connect(|args| {
-			unsafe {
-				let node = &*args.0;
+	/// Whether `ancestor` is `id` or one of its parents.
+	pub fn is_ancestor(&self, ancestor: NodeId, id: NodeId) -> bool {
+		let mut current = Some(id);
 
-				let scene = node.scene().unwrap();
-				if let Ok(scene) = scene.read() {
-					scene.node_component_added.emit((args.0, args.1));
-				}
+		while let Some(node) = current {
+			if node == ancestor {
+				return true;
 			}
-		});
 
-		node.component_removed.connect(|args| {
-			unsafe {
-				let node = &*args.0;
+			current = self.child_parent.get(&node).copied();
+		}
 
-				let scene = node.scene().unwrap();
-				if let Ok(scene) = scene.read() {
-					scene.node_component_removed.emit((args.0, args.1));
-				}
-			}
+		false
+	}
+
+	/// Adds `node` as the last child of `parent`, or of the root if it's `None`. Panics if `parent` was removed.
+	pub fn add_node(&mut self, node: Node, parent: Option<NodeId>) -> NodeId {
+		self.insert_node_at(node, parent, usize::MAX)
+	}
+
+	/// Same as `add_node`, but puts the node at `index` among its siblings. Indices past the end append it.
+	pub fn insert_node_at(&mut self, mut node: Node, parent: Option<NodeId>, index: usize) -> NodeId {
+		let parent = parent.unwrap_or(self.root);
+		assert!(self.contains(parent), "Can't add a node to {}, it's not in the scene", parent);
+
+		self.update_indices();
+
+		let new_id = self.allocate_id();
+		self.attach(new_id, parent, index);
+
+		node.enter_scene(new_id, self.this.as_ref().unwrap().clone());
+		node.changed = Some(self.changed.clone());
+		node.removals = Some(self.removals.clone());
+		self.names.borrow_mut().insert(new_id, node.name());
+		let (joined, _) = self.groups.borrow_mut().set(new_id, node.groups());
+		self.components.borrow_mut().set(new_id, &node.component_types());
+
+		// nodes in the scene are usually changed through a write lock on it, so the scene can't be locked here.
+		// The scene never moves, see `SceneGraph`, and the handlers are cleared when the node leaves it.
+		let this = self as *const Self;
+
+		node.component_added.connect(move |args| {
+			unsafe { (*this).node_component_added.emit(*args); }
+		});
+
+		node.component_removed.connect(move |args| {
+			unsafe { (*this).node_component_removed.emit(*args); }
 		});
 
 		self.node_added.emit((self, &node));
 		self.nodes.insert(new_id, node);
+
+		for group in joined {
+			self.node_added_to_group.emit((self, &self.nodes[&new_id], group));
+		}
+
 		new_id
 	}
 
-	pub fn remove_node(&mut self, node: Node) {
+	/// Removes the node and all of its children. Returns `false` if the node isn't in the scene anymore.
+	/// The root can't be removed.
+	pub fn remove_node(&mut self, id: NodeId) -> bool {
+		if !self.contains(id) {
+			return false;
+		}
+
+		if id == self.root {
+			log::warn!("Can't remove the scene root");
+			return false;
+		}
+
 		let self_ptr = self as *const Self;
+		self.update_indices();
 
-		// get this node and all of its children with the post order DFS algorithm and remove them
-		for node in ScenePostDfsIterator::new(self.this.clone().unwrap(), node.id()) {
-			{
-				if let Some(node) = self.nodes.get_mut(&node) {
-					node.exit_scene();
-					self.node_removed.emit((self_ptr, node));
+		// children go before their parents, and each node leaves its parent right after, so what's left of the
+		// subtree is still whole
+		for node in self.subtree_post_order(id) {
+			let groups = self.groups.borrow_mut().remove(node);
+
+			if let Some(node) = self.nodes.get_mut(&node) {
+				for group in groups {
+					self.node_removed_from_group.emit((self_ptr, node, group));
 				}
+
+				// handlers still see the node's id and its components' owners
+				self.node_removed.emit((self_ptr, node));
+				node.exit_scene();
 			}
 
-			// TODO uhhhh not 100% sure if this won't hit some concurrency shit and mess up the iterator
+			self.detach(node);
 			self.nodes.remove(&node);
+			self.names.borrow_mut().remove(node);
+			self.components.borrow_mut().remove(node);
+			self.parent_children.remove(&node);
+			self.release_id(node);
+		}
+
+		true
+	}
+
+	/// Marks the node to be removed along with its children at the next `free_queued`, e.g. from its own update
+	/// handlers or while iterating the scene. Only needs a read lock. Returns `false` if the node isn't in the
+	/// scene or is the root.
+	pub fn queue_remove(&self, id: NodeId) -> bool {
+		if !self.contains(id) || id == self.root {
+			return false;
+		}
+
+		let mut removals = self.removals.lock().unwrap();
+
+		if !removals.contains(&id) {
+			removals.push(id);
+		}
+
+		true
+	}
+
+	pub fn is_queued_for_removal(&self, id: NodeId) -> bool {
+		self.removals.lock().unwrap().contains(&id)
+	}
+
+	/// Removes the nodes marked with `queue_remove` or `Node::queue_free`, in the order they were marked.
+	/// Returns how many were removed, nodes already gone with a parent that was marked before them aren't counted.
+	pub fn free_queued(&mut self) -> usize {
+		let mut removed = 0;
+
+		// nodes can be marked while others are removed, e.g. by `exit_tree` handlers
+		loop {
+			let queued: Vec<NodeId> = self.removals.lock().unwrap().drain(..).collect();
+
+			if queued.is_empty() {
+				return removed;
+			}
+
+			for id in queued {
+				if self.remove_node(id) {
+					removed += 1;
+				}
+			}
+		}
+	}
+
+	/// Moves the node and its children to the end of `new_parent`'s children. Fails if either node isn't in the
+	/// scene, if `node` is the root, or if `new_parent` is `node` itself or one of its children.
+	pub fn reparent(&mut self, node: NodeId, new_parent: NodeId) -> bool {
+		if !self.contains(node) || !self.contains(new_parent) || node == self.root {
+			return false;
+		}
+
+		if self.is_ancestor(node, new_parent) {
+			log::warn!("Can't move {} under {}, it's one of its own children", node, new_parent);
+			return false;
+		}
+
+		let old_index = self.index_in_parent(node).unwrap();
+		let old_parent = self.detach(node);
+		self.attach(node, new_parent, usize::MAX);
+
+		self.emit_moved(node, old_parent, old_index);
+		true
+	}
+
+	/// Moves the child at `from` to `to` among `parent`'s children, shifting the ones in between.
+	pub fn move_child(&mut self, parent: NodeId, from: usize, to: usize) -> bool {
+		let Some(children) = self.parent_children.get_mut(&parent) else { return false; };
+
+		if from >= children.len() || to >= children.len() {
+			return false;
+		}
+
+		let child = children.remove(from);
+		children.insert(to, child);
+
+		if from != to {
+			self.emit_moved(child, parent, from);
+		}
+
+		true
+	}
+
+	/// Swaps the places of two children of the same parent.
+	pub fn swap_siblings(&mut self, a: NodeId, b: NodeId) -> bool {
+		let (Some(parent), Some(parent_b)) = (self.child_parent.get(&a).copied(), self.child_parent.get(&b).copied()) else {
+			return false;
+		};
+
+		if parent != parent_b {
+			return false;
+		}
+
+		if a == b {
+			return true;
+		}
+
+		let children = self.parent_children.get_mut(&parent).unwrap();
+		let index_a = children.iter().position(|child| *child == a).unwrap();
+		let index_b = children.iter().position(|child| *child == b).unwrap();
+		children.swap(index_a, index_b);
+
+		self.emit_moved(a, parent, index_a);
+		self.emit_moved(b, parent, index_b);
+		true
+	}
+
+	fn attach(&mut self, id: NodeId, parent: NodeId, index: usize) {
+		let children = self.parent_children.entry(parent).or_default();
+		children.insert(index.min(children.len()), id);
+
+		self.child_parent.insert(id, parent);
+	}
+
+	/// Takes the node out of its parent's children, returns the parent.
+	fn detach(&mut self, id: NodeId) -> NodeId {
+		let parent = self.child_parent.remove(&id)
+			.expect("How did we end up with a lost little lamb with no parent?");
+
+		if let Some(siblings) = self.parent_children.get_mut(&parent) {
+			// removed subtrees leave from the back
+			if siblings.last() == Some(&id) {
+				siblings.pop();
+			} else {
+				siblings.retain(|sibling| *sibling != id);
+			}
+		}
+
+		parent
+	}
+
+	fn emit_moved(&self, id: NodeId, old_parent: NodeId, old_index: usize) {
+		if let Some(node) = self.nodes.get(&id) {
+			self.node_moved.emit((self, node, old_parent, old_index));
+		}
+	}
+
+	/// Children before their parents, last siblings first.
+	fn subtree_post_order(&self, root: NodeId) -> Vec<NodeId> {
+		let mut order = Vec::new();
+		let mut stack = vec![(root, false)];
+
+		while let Some((node, visited)) = stack.pop() {
+			if visited {
+				order.push(node);
+				continue;
+			}
+
+			stack.push((node, true));
+
+			for child in self.children_slice(node) {
+				stack.push((*child, false));
+			}
+		}
+
+		order
+	}
+
+	fn allocate_id(&mut self) -> NodeId {
+		if let Some(index) = self.free.pop() {
+			return NodeId::new(index, self.generations[index as usize]);
+		}
+
+		self.generations.push(0);
+		NodeId::new(self.generations.len() as u32 - 1, 0)
+	}
+
+	fn release_id(&mut self, id: NodeId) {
+		let generation = &mut self.generations[id.index() as usize];
+
+		// a slot whose generation ran out is retired instead of handing out an id that was used before
+		if let Some(next) = generation.checked_add(1) {
+			*generation = next;
+			self.free.push(id.index());
 		}
 	}
 }
@@ -0,0 +1,193 @@
+use std::{collections::HashMap, time::Duration};
+
+use crate::{ComponentQuery, NodeId, SceneGraph};
+
+/// When in a frame a system runs. Stages run in this order, with node updates between `PreUpdate` and `Update`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Stage {
+	PreUpdate,
+	Update,
+	PostUpdate,
+	PreRender
+}
+
+impl Stage {
+	pub const ALL: [Stage; 4] = [Stage::PreUpdate, Stage::Update, Stage::PostUpdate, Stage::PreRender];
+}
+
+type SystemFn = Box<dyn FnMut(&mut SceneGraph, Duration)>;
+
+/// Per-frame logic run on the scene by a `Schedule`.
+pub struct System {
+	name: String,
+	stage: Stage,
+
+	before: Vec<String>,
+	after: Vec<String>,
+
+	run: SystemFn
+}
+
+impl System {
+	pub fn new<F: FnMut(&mut SceneGraph, Duration) + 'static>(name: &str, stage: Stage, run: F) -> Self {
+		Self {
+			name: name.to_string(),
+			stage,
+			before: Vec::new(),
+			after: Vec::new(),
+			run: Box::new(run)
+		}
+	}
+
+	/// A system calling `f` with every node matching `Q`, see `SceneGraph::query`.
+	pub fn query<Q, F>(name: &str, stage: Stage, mut f: F) -> Self
+	where
+		Q: ComponentQuery + 'static,
+		F: FnMut(NodeId, Q::ItemMut<'_>, Duration) + 'static
+	{
+		Self::new(name, stage, move |scene, delta| {
+			scene.query_mut::<Q, _>(|id, components| f(id, components, delta));
+		})
+	}
+
+	/// Runs before the system named `other`, if it's in the same stage.
+	pub fn before(mut self, other: &str) -> Self {
+		self.before.push(other.to_string());
+		self
+	}
+
+	/// Runs after the system named `other`, if it's in the same stage.
+	pub fn after(mut self, other: &str) -> Self {
+		self.after.push(other.to_string());
+		self
+	}
+
+	pub fn name(&self) -> &str { &self.name }
+	pub fn stage(&self) -> Stage { self.stage }
+}
+
+/// Systems by stage, in an order satisfying their `before` and `after` constraints. Systems without
+/// constraints between them run in the order they were added. Constraints naming systems that aren't
+/// in the schedule are ignored, so they can be added in any order.
+#[derive(Default)]
+pub struct Schedule {
+	systems: Vec<System>,
+	// indices into `systems`, in the order they run
+	order: HashMap<Stage, Vec<usize>>
+}
+
+impl Schedule {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Fails if there already is a system with the same name, or if its constraints contradict the others'.
+	pub fn add(&mut self, system: System) -> bool {
+		if self.contains(&system.name) {
+			log::warn!("There already is a system named {}", system.name);
+			return false;
+		}
+
+		let stage = system.stage;
+		self.systems.push(system);
+
+		match self.sort(stage) {
+			Ok(order) => {
+				self.order.insert(stage, order);
+				true
+			},
+			Err(cycle) => {
+				let system = self.systems.pop().unwrap();
+				log::warn!("Can't add system {}, the ordering constraints of {} form a cycle", system.name, cycle.join(", "));
+				false
+			}
+		}
+	}
+
+	pub fn remove(&mut self, name: &str) -> bool {
+		let Some(index) = self.systems.iter().position(|system| system.name == name) else { return false; };
+
+		self.systems.remove(index);
+
+		// taking a system out can't introduce a cycle, but indices after it shifted
+		for stage in Stage::ALL {
+			let order = self.sort(stage).unwrap();
+			self.order.insert(stage, order);
+		}
+
+		true
+	}
+
+	pub fn contains(&self, name: &str) -> bool {
+		self.systems.iter().any(|system| system.name == name)
+	}
+
+	/// Names of the systems in the stage, in the order they run.
+	pub fn systems(&self, stage: Stage) -> Vec<&str> {
+		self.order.get(&stage).map_or_else(Vec::new, |order| {
+			order.iter().map(|index| self.systems[*index].name.as_str()).collect()
+		})
+	}
+
+	pub fn run_stage(&mut self, stage: Stage, scene: &mut SceneGraph, delta: Duration) {
+		let Some(order) = self.order.get(&stage) else { return; };
+
+		for index in order {
+			(self.systems[*index].run)(scene, delta);
+		}
+	}
+
+	/// Runs every stage in order.
+	pub fn run(&mut self, scene: &mut SceneGraph, delta: Duration) {
+		for stage in Stage::ALL {
+			self.run_stage(stage, scene, delta);
+		}
+	}
+
+	/// Orders the systems in the stage, or returns the ones stuck in a cycle.
+	fn sort(&self, stage: Stage) -> Result<Vec<usize>, Vec<String>> {
+		let indices: Vec<usize> = (0..self.systems.len())
+			.filter(|index| self.systems[*index].stage == stage)
+			.collect();
+
+		let position = |name: &String| indices.iter().position(|index| self.systems[*index].name == *name);
+
+		// edges between positions in `indices`, from the system running first
+		let mut next: Vec<Vec<usize>> = vec![Vec::new(); indices.len()];
+		let mut incoming = vec![0; indices.len()];
+
+		for (from, index) in indices.iter().enumerate() {
+			let system = &self.systems[*index];
+
+			let edges = system.before.iter().filter_map(position).map(|to| (from, to))
+				.chain(system.after.iter().filter_map(position).map(|to| (to, from)));
+
+			for (from, to) in edges {
+				next[from].push(to);
+				incoming[to] += 1;
+			}
+		}
+
+		let mut order = Vec::with_capacity(indices.len());
+		let mut done = vec![false; indices.len()];
+
+		// always take the earliest added system that's free to run, so unrelated systems keep their order
+		while let Some(ready) = (0..indices.len()).find(|i| !done[*i] && incoming[*i] == 0) {
+			done[ready] = true;
+			order.push(indices[ready]);
+
+			for to in &next[ready] {
+				incoming[*to] -= 1;
+			}
+		}
+
+		if order.len() < indices.len() {
+			return Err((0..indices.len())
+				.filter(|i| !done[*i])
+				.map(|i| self.systems[indices[i]].name.clone())
+				.collect());
+		}
+
+		Ok(order)
+	}
+}
@@ -0,0 +1,384 @@
+use std::{any::TypeId, collections::{HashMap, HashSet}, fs, path::{Path, PathBuf}};
+
+use ron::{ser::PrettyConfig, value::RawValue};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{Node, NodeComponent, NodeId, NodeTree, NodeTreeEntry, PrefabInstance, PrefabLink, PrefabOverride, ProcessSettings, SceneGraph, SharedSceneGraph, error::{ErrorKind, SceneError}, reflect::Value, tree};
+
+/// A component that can be saved to a scene file. `#[derive(NodeComponent)]` implements it for types
+/// marked with `#[component(serialize)]`, the type still has to be registered in a `ComponentRegistry`.
+pub trait SerializableComponent: NodeComponent + Serialize + DeserializeOwned {
+	/// What the component is called in scene files, the type name unless given with `#[component(serialize = "...")]`.
+	const TYPE_NAME: &'static str;
+}
+
+type SerializeFn = Box<dyn Fn(&dyn NodeComponent) -> Result<Box<RawValue>, SceneError>>;
+type DeserializeFn = Box<dyn Fn(&RawValue) -> Result<Vec<Box<dyn NodeComponent>>, SceneError>>;
+
+struct Registration {
+	name: String,
+	serialize: SerializeFn
+}
+
+/// The components that can be saved and loaded, by type and by the name they're saved under.
+#[derive(Default)]
+pub struct ComponentRegistry {
+	by_type: HashMap<TypeId, Registration>,
+	by_name: HashMap<String, DeserializeFn>,
+	types: HashMap<String, TypeId>,
+	skipped: HashSet<TypeId>
+}
+
+impl ComponentRegistry {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn register<T: SerializableComponent>(&mut self) {
+		self.insert::<T, T>(T::TYPE_NAME, to_raw, |component| Ok(vec![Box::new(component)]));
+	}
+
+	/// Registers a component that's saved as whatever `to_data` turns it into. `from_data` may create more than
+	/// one component, for components that depend on ones that can't be saved by themselves.
+	pub fn register_with<T, D, FS, FD>(&mut self, name: &str, to_data: FS, from_data: FD)
+		where
+			T: NodeComponent, D: Serialize + DeserializeOwned,
+			FS: Fn(&T) -> Result<D, SceneError> + 'static,
+			FD: Fn(D) -> Result<Vec<Box<dyn NodeComponent>>, SceneError> + 'static
+	{
+		self.insert::<T, D>(name, move |component| to_raw(&to_data(component)?), from_data);
+	}
+
+	fn insert<T: NodeComponent, D: DeserializeOwned>(
+		&mut self,
+		name: &str,
+		serialize: impl Fn(&T) -> Result<Box<RawValue>, SceneError> + 'static,
+		from_data: impl Fn(D) -> Result<Vec<Box<dyn NodeComponent>>, SceneError> + 'static
+	) {
+		let serialize: SerializeFn = Box::new(move |component| {
+			serialize(component.as_any().downcast_ref::<T>().expect("Component registered under the wrong type"))
+				.map_err(|e| SceneError::new(e.kind().clone(), &format!("Failed to serialize {}: {}", component.name(), e.msg())))
+		});
+
+		let type_name = name.to_string();
+		let deserialize: DeserializeFn = Box::new(move |data| {
+			let data = data.into_rust::<D>()
+				.map_err(|e| SceneError::new(ErrorKind::DeserializationError, &format!("Failed to deserialize {}: {}", type_name, e)))?;
+
+			from_data(data)
+		});
+
+		self.by_type.insert(TypeId::of::<T>(), Registration { name: name.to_string(), serialize });
+		self.by_name.insert(name.to_string(), deserialize);
+		self.types.insert(name.to_string(), TypeId::of::<T>());
+	}
+
+	/// Leaves components of this type out of saved scenes without a warning, for components that are
+	/// recreated by another one when it's loaded.
+	pub fn skip<T: NodeComponent>(&mut self) {
+		self.skipped.insert(TypeId::of::<T>());
+	}
+
+	pub fn is_registered(&self, name: &str) -> bool {
+		self.by_name.contains_key(name)
+	}
+
+	fn serialize(&self, component: &dyn NodeComponent) -> Result<Option<ComponentData>, SceneError> {
+		let type_id = component.as_any().type_id();
+
+		let Some(registration) = self.by_type.get(&type_id) else {
+			if !self.skipped.contains(&type_id) {
+				log::warn!("{} is not registered, it won't be saved", component.name());
+			}
+
+			return Ok(None);
+		};
+
+		Ok(Some(ComponentData {
+			kind: registration.name.clone(),
+			data: (registration.serialize)(component)?
+		}))
+	}
+
+	fn link_data(&self, link: &PrefabLink) -> Result<PrefabData, SceneError> {
+		let mut overrides = Vec::new();
+
+		for r#override in &link.overrides {
+			match r#override {
+				PrefabOverride::Field { node, component, field, value } => {
+					let registration = self.by_type.get(component)
+						.ok_or_else(|| SceneError::new(ErrorKind::UnknownComponent, &format!("Can't save override of {}, its component is not registered", field)))?;
+
+					overrides.push(OverrideData::Field {
+						node: node.clone(),
+						component: registration.name.clone(),
+						field: field.clone(),
+						value: value.clone()
+					});
+				},
+				PrefabOverride::Component { node, component } => {
+					if let Some(component) = self.serialize(component.get())? {
+						overrides.push(OverrideData::Component { node: node.clone(), component });
+					}
+				}
+			}
+		}
+
+		Ok(PrefabData { path: link.path.clone(), overrides })
+	}
+
+	fn link(&self, data: &PrefabData) -> Result<PrefabLink, SceneError> {
+		let mut link = PrefabLink::new(&data.path);
+
+		for r#override in &data.overrides {
+			match r#override {
+				OverrideData::Field { node, component, field, value } => {
+					let component = *self.types.get(component)
+						.ok_or_else(|| SceneError::new(ErrorKind::UnknownComponent, &format!("No component registered as {}", component)))?;
+
+					link.overrides.push(PrefabOverride::Field { node: node.clone(), component, field: field.clone(), value: value.clone() });
+				},
+				OverrideData::Component { node, component } => {
+					for component in self.deserialize(component)? {
+						let name = component.name().to_string();
+						let r#override = PrefabOverride::component(node, component)
+							.ok_or_else(|| SceneError::new(ErrorKind::UncloneableComponent, &format!("Can't override {} with a {}, it can't be cloned", node, name)))?;
+
+						link.overrides.push(r#override);
+					}
+				}
+			}
+		}
+
+		Ok(link)
+	}
+
+	fn deserialize(&self, data: &ComponentData) -> Result<Vec<Box<dyn NodeComponent>>, SceneError> {
+		let deserialize = self.by_name.get(&data.kind)
+			.ok_or_else(|| SceneError::new(ErrorKind::UnknownComponent, &format!("No component registered as {}", data.kind)))?;
+
+		deserialize(&data.data)
+	}
+}
+
+fn to_raw<D: Serialize>(data: &D) -> Result<Box<RawValue>, SceneError> {
+	RawValue::from_rust(data)
+		.map_err(|e| SceneError::new(ErrorKind::SerializationError, &e.to_string()))
+}
+
+#[derive(Serialize, Deserialize)]
+struct ComponentData {
+	#[serde(rename = "type")]
+	kind: String,
+	data: Box<RawValue>
+}
+
+#[derive(Serialize, Deserialize)]
+enum OverrideData {
+	Field { node: String, component: String, field: String, value: Value },
+	Component { node: String, component: ComponentData }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PrefabData {
+	path: PathBuf,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	overrides: Vec<OverrideData>
+}
+
+/// A node, or an instance of a prefab with only what it changes saved.
+#[derive(Serialize, Deserialize)]
+struct NodeData {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	name: Option<String>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	groups: Vec<String>,
+	#[serde(default, skip_serializing_if = "ProcessSettings::is_default")]
+	process: ProcessSettings,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	prefab: Option<PrefabData>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	components: Vec<ComponentData>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	children: Vec<NodeData>
+}
+
+/// What's in a `.scene` file.
+#[derive(Serialize, Deserialize)]
+struct SceneFile {
+	root: NodeData
+}
+
+impl SceneFile {
+	fn to_ron(&self) -> Result<String, SceneError> {
+		ron::ser::to_string_pretty(self, PrettyConfig::default())
+			.map_err(|e| SceneError::new(ErrorKind::SerializationError, &format!("Failed to serialize scene: {}", e)))
+	}
+
+	fn from_ron(ron: &str) -> Result<Self, SceneError> {
+		ron::from_str(ron)
+			.map_err(|e| SceneError::new(ErrorKind::DeserializationError, &format!("Failed to deserialize scene: {}", e)))
+	}
+}
+
+fn read_file<P: AsRef<Path>>(path: P) -> Result<String, SceneError> {
+	fs::read_to_string(&path)
+		.map_err(|e| SceneError::with_path(&path, ErrorKind::IoError, &format!("Failed to read scene file: {}", e)))
+}
+
+fn write_file<P: AsRef<Path>>(path: P, ron: String) -> Result<(), SceneError> {
+	fs::write(&path, ron)
+		.map_err(|e| SceneError::with_path(&path, ErrorKind::IoError, &format!("Failed to write scene file: {}", e)))
+}
+
+impl NodeTree {
+	pub fn to_ron(&self, registry: &ComponentRegistry) -> Result<String, SceneError> {
+		fn entry_data(entry: &NodeTreeEntry, registry: &ComponentRegistry) -> Result<NodeData, SceneError> {
+			if let Some(link) = &entry.prefab {
+				return Ok(NodeData {
+					name: entry.name.clone(),
+					groups: entry.groups.clone(),
+					process: entry.process,
+					prefab: Some(registry.link_data(link)?),
+					components: Vec::new(),
+					children: Vec::new()
+				});
+			}
+
+			let mut components = Vec::new();
+
+			for component in &entry.components {
+				components.extend(registry.serialize(component.as_ref())?);
+			}
+
+			Ok(NodeData {
+				name: entry.name.clone(),
+				groups: entry.groups.clone(),
+				process: entry.process,
+				prefab: None,
+				components,
+				children: entry.children.iter()
+					.map(|child| entry_data(child, registry))
+					.collect::<Result<_, _>>()?
+			})
+		}
+
+		SceneFile { root: entry_data(&self.root, registry)? }.to_ron()
+	}
+
+	/// Prefab instances in the tree are left empty until filled in with `PrefabLibrary::expand`.
+	pub fn from_ron(ron: &str, registry: &ComponentRegistry) -> Result<Self, SceneError> {
+		fn entry(data: &NodeData, registry: &ComponentRegistry) -> Result<NodeTreeEntry, SceneError> {
+			let mut components = Vec::new();
+
+			for component in &data.components {
+				components.extend(registry.deserialize(component)?);
+			}
+
+			Ok(NodeTreeEntry {
+				name: data.name.clone(),
+				groups: data.groups.clone(),
+				process: data.process,
+				prefab: data.prefab.as_ref().map(|prefab| registry.link(prefab)).transpose()?,
+				components,
+				children: data.children.iter()
+					.map(|child| entry(child, registry))
+					.collect::<Result<_, _>>()?
+			})
+		}
+
+		let file = SceneFile::from_ron(ron)?;
+		Ok(Self { root: entry(&file.root, registry)? })
+	}
+
+	pub fn save<P: AsRef<Path>>(&self, path: P, registry: &ComponentRegistry) -> Result<(), SceneError> {
+		write_file(path, self.to_ron(registry)?)
+	}
+
+	pub fn load<P: AsRef<Path>>(path: P, registry: &ComponentRegistry) -> Result<Self, SceneError> {
+		Self::from_ron(&read_file(path)?, registry)
+	}
+}
+
+impl SceneGraph {
+	/// The whole scene in the `.scene` format, the root's components included.
+	pub fn to_ron(&self, registry: &ComponentRegistry) -> Result<String, SceneError> {
+		fn node_data(scene: &SceneGraph, id: NodeId, registry: &ComponentRegistry) -> Result<NodeData, SceneError> {
+			let node = scene.node(id).unwrap();
+
+			if let Some(instance) = node.component::<PrefabInstance>() {
+				return Ok(NodeData {
+					name: Some(node.name().to_string()),
+					groups: node.groups().to_vec(),
+					process: node.process_settings(),
+					prefab: Some(registry.link_data(instance.link())?),
+					components: Vec::new(),
+					children: Vec::new()
+				});
+			}
+
+			let mut components = Vec::new();
+
+			for component in node.components() {
+				components.extend(registry.serialize(component.as_ref())?);
+			}
+
+			Ok(NodeData {
+				name: Some(node.name().to_string()),
+				groups: node.groups().to_vec(),
+				process: node.process_settings(),
+				prefab: None,
+				components,
+				children: scene.children_slice(id).iter()
+					.map(|child| node_data(scene, *child, registry))
+					.collect::<Result<_, _>>()?
+			})
+		}
+
+		SceneFile { root: node_data(self, self.root_id(), registry)? }.to_ron()
+	}
+
+	pub fn save<P: AsRef<Path>>(&self, path: P, registry: &ComponentRegistry) -> Result<(), SceneError> {
+		write_file(path, self.to_ron(registry)?)
+	}
+
+	/// A new scene made from `tree`. Its root entry becomes the scene root.
+	pub fn from_tree(tree: &NodeTree) -> SharedSceneGraph {
+		let scene = Self::new();
+
+		{
+			let mut graph = scene.write().unwrap();
+			let root = graph.root_id();
+			let node: &mut Node = graph.node_mut(root).unwrap();
+
+			if let Some(name) = &tree.root.name {
+				node.set_name(name);
+			}
+
+			for group in &tree.root.groups {
+				node.add_to_group(group);
+			}
+
+			node.set_process_settings(tree.root.process);
+
+			for component in tree::clone_components(&tree.root.components) {
+				node.add_component(component);
+			}
+		}
+
+		for child in &tree.root.children {
+			NodeTree::instantiate_entry(child, &scene, None);
+		}
+
+		scene
+	}
+
+	/// Prefab instances in the scene are left empty, use `PrefabLibrary::load_scene` for scenes with them.
+	pub fn from_ron(ron: &str, registry: &ComponentRegistry) -> Result<SharedSceneGraph, SceneError> {
+		Ok(Self::from_tree(&NodeTree::from_ron(ron, registry)?))
+	}
+
+	pub fn load<P: AsRef<Path>>(path: P, registry: &ComponentRegistry) -> Result<SharedSceneGraph, SceneError> {
+		Self::from_ron(&read_file(path)?, registry)
+	}
+}
@@ -0,0 +1,205 @@
+use std::{any::{Any, TypeId}, collections::HashMap, iter, ops::Range};
+
+use crate::{NodeComponent, NodeId, SceneGraph, reflect::{FieldInfo, Value}, tree};
+
+/// How a component is captured by `SceneSnapshot`, see `NodeComponent::snapshot_mode`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotMode {
+	/// By its reflected fields that aren't read-only. Restoring sets the ones that changed, through the component's
+	/// setters.
+	Fields,
+	/// As a clone, which replaces the component when restored.
+	Clone,
+	Skip
+}
+
+/// A change between two snapshots, see `SceneSnapshot::diff`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnapshotChange {
+	Field { node: NodeId, component: TypeId, field: &'static str, from: Value, to: Value },
+	/// The component is only in the later snapshot.
+	Added { node: NodeId, component: TypeId },
+	/// The component is only in the earlier snapshot.
+	Removed { node: NodeId, component: TypeId }
+}
+
+/// The state of a scene's components at one point, to go back to later, e.g. for rollback netcode or rewinding.
+/// Only components are captured, not the nodes they're on: restoring leaves nodes added since alone, and nodes
+/// removed since can't come back.
+pub struct SceneSnapshot {
+	components: Vec<Captured>,
+	// field values of the components captured by their fields, back to back
+	values: Vec<Value>
+}
+
+struct Captured {
+	node: NodeId,
+	type_id: TypeId,
+	state: CapturedState
+}
+
+enum CapturedState {
+	/// Values of the fields that aren't read-only, in order.
+	Fields { fields: &'static [FieldInfo], values: Range<usize> },
+	Clone(Box<dyn NodeComponent>)
+}
+
+impl SceneSnapshot {
+	/// Every component in the scene that can be captured.
+	pub fn capture(scene: &SceneGraph) -> Self {
+		Self::capture_filtered(scene, |_| true)
+	}
+
+	/// Only components of the given types, e.g. the ones a rollback has to cover.
+	pub fn capture_components(scene: &SceneGraph, types: &[TypeId]) -> Self {
+		Self::capture_filtered(scene, |type_id| types.contains(&type_id))
+	}
+
+	/// How many components were captured.
+	pub fn len(&self) -> usize { self.components.len() }
+	pub fn is_empty(&self) -> bool { self.components.is_empty() }
+
+	/// Puts the captured state back into the scene. Returns how many components changed, components captured as
+	/// clones always count. Missing components captured as clones are added back, ones captured by their fields
+	/// can't be.
+	pub fn restore(&self, scene: &mut SceneGraph) -> usize {
+		let mut restored = 0;
+
+		for captured in &self.components {
+			let Some(node) = scene.node_mut(captured.node) else { continue; };
+
+			match &captured.state {
+				CapturedState::Fields { fields, values } => {
+					let Some(reflect) = node.component_of_type_mut(captured.type_id).and_then(|component| component.reflect_mut()) else {
+						continue;
+					};
+
+					let mut changed = false;
+
+					for (field, value) in writable(fields).zip(&self.values[values.clone()]) {
+						if reflect.field(field.name).as_ref() == Some(value) {
+							continue;
+						}
+
+						match reflect.set_field(field.name, value.clone()) {
+							Ok(()) => changed = true,
+							Err(e) => log::warn!("Failed to restore {} of node {}: {}", field.name, captured.node, e)
+						}
+					}
+
+					if changed {
+						restored += 1;
+					}
+				},
+				CapturedState::Clone(component) => {
+					let mut component = component.clone_component().unwrap();
+					let shared = node.scene();
+
+					match node.component_of_type_mut(captured.type_id) {
+						// in place, so it doesn't look like the component was removed and added again
+						Some(current) => {
+							if let Some(scene) = shared {
+								component.enter_scene(captured.node, scene);
+							}
+
+							std::mem::replace(current, component).exit_scene();
+						},
+						None => { node.add_component(component); }
+					}
+
+					restored += 1;
+				}
+			}
+		}
+
+		restored
+	}
+
+	/// What changed from this snapshot to `later`. Fields are compared for components captured by them, components
+	/// captured as clones are only reported when they're added or removed.
+	pub fn diff(&self, later: &SceneSnapshot) -> Vec<SnapshotChange> {
+		let earlier: HashMap<(NodeId, TypeId), &Captured> = self.components.iter()
+			.map(|captured| ((captured.node, captured.type_id), captured))
+			.collect();
+
+		let mut changes = Vec::new();
+		let mut found = 0;
+
+		for captured in &later.components {
+			let Some(before) = earlier.get(&(captured.node, captured.type_id)) else {
+				changes.push(SnapshotChange::Added { node: captured.node, component: captured.type_id });
+				continue;
+			};
+
+			found += 1;
+
+			let (CapturedState::Fields { fields, values: from }, CapturedState::Fields { values: to, .. }) = (&before.state, &captured.state) else {
+				continue;
+			};
+
+			for (field, (from, to)) in writable(fields).zip(self.values[from.clone()].iter().zip(&later.values[to.clone()])) {
+				if from != to {
+					changes.push(SnapshotChange::Field {
+						node: captured.node,
+						component: captured.type_id,
+						field: field.name,
+						from: from.clone(),
+						to: to.clone()
+					});
+				}
+			}
+		}
+
+		if found < self.components.len() {
+			let later: HashMap<(NodeId, TypeId), ()> = later.components.iter()
+				.map(|captured| ((captured.node, captured.type_id), ()))
+				.collect();
+
+			changes.extend(self.components.iter()
+				.filter(|captured| !later.contains_key(&(captured.node, captured.type_id)))
+				.map(|captured| SnapshotChange::Removed { node: captured.node, component: captured.type_id }));
+		}
+
+		changes
+	}
+
+	fn capture_filtered<F: Fn(TypeId) -> bool>(scene: &SceneGraph, filter: F) -> Self {
+		let mut snapshot = Self { components: Vec::new(), values: Vec::new() };
+
+		for id in iter::once(scene.root_id()).chain(scene.descendants(scene.root_id())) {
+			for component in scene.node(id).unwrap().components() {
+				let type_id = Any::type_id(component.as_any());
+
+				if !filter(type_id) {
+					continue;
+				}
+
+				let state = match component.snapshot_mode() {
+					SnapshotMode::Fields => {
+						let Some(reflect) = component.reflect() else { continue; };
+						let start = snapshot.values.len();
+
+						for field in writable(reflect.fields()) {
+							snapshot.values.push(reflect.field(field.name).unwrap());
+						}
+
+						CapturedState::Fields { fields: reflect.fields(), values: start..snapshot.values.len() }
+					},
+					SnapshotMode::Clone => {
+						let Some(clone) = tree::copy_out_of_scene(component.as_ref()) else { continue; };
+						CapturedState::Clone(clone)
+					},
+					SnapshotMode::Skip => continue
+				};
+
+				snapshot.components.push(Captured { node: id, type_id, state });
+			}
+		}
+
+		snapshot
+	}
+}
+
+fn writable(fields: &'static [FieldInfo]) -> impl Iterator<Item = &'static FieldInfo> {
+	fields.iter().filter(|field| !field.read_only)
+}
@@ -1,10 +1,14 @@
-use std::sync::RwLockWriteGuard;
+use std::path::Path;
 
-use serde::{Deserialize, Serialize};
-
-use crate::{Node, NodeComponent, NodeId, SceneGraph, SharedSceneGraph};
+use crate::{Node, NodeComponent, NodeId, PrefabInstance, PrefabLink, ProcessSettings, SceneGraph, SharedSceneGraph, error::{ErrorKind, SceneError}};
 
 pub struct NodeTreeEntry {
+	/// Nodes without a name get a random one.
+	pub name: Option<String>,
+	pub groups: Vec<String>,
+	pub process: ProcessSettings,
+	/// Set for instances of a prefab, whose components and children come from it. See `PrefabLibrary`.
+	pub prefab: Option<PrefabLink>,
 	pub components: Vec<Box<dyn NodeComponent>>,
 	pub children: Vec<NodeTreeEntry>,
 }
@@ -12,12 +16,54 @@ pub struct NodeTreeEntry {
 impl NodeTreeEntry {
 	pub fn new() -> Self {
 		Self {
+			name: None,
+			groups: Vec::new(),
+			process: ProcessSettings::default(),
+			prefab: None,
 			components: Vec::new(),
 			children: Vec::new()
 		}
 	}
+
+	pub fn with_name(name: &str) -> Self {
+		Self {
+			name: Some(name.to_string()),
+			..Self::new()
+		}
+	}
+
+	/// An instance of the prefab at `path`, filled in by `PrefabLibrary::expand`.
+	pub fn instance<P: AsRef<Path>>(path: P) -> Self {
+		Self {
+			prefab: Some(PrefabLink::new(path)),
+			..Self::new()
+		}
+	}
+
+	/// Calls `f` on the entry and everything below it.
+	pub fn visit<F: FnMut(&NodeTreeEntry)>(&self, f: &mut F) {
+		f(self);
+
+		for child in &self.children {
+			child.visit(f);
+		}
+	}
+}
+
+impl Clone for NodeTreeEntry {
+	fn clone(&self) -> Self {
+		Self {
+			name: self.name.clone(),
+			groups: self.groups.clone(),
+			process: self.process,
+			prefab: self.prefab.clone(),
+			components: clone_components(&self.components),
+			children: self.children.clone()
+		}
+	}
 }
 
+#[derive(Clone)]
 pub struct NodeTree {
 	pub root: NodeTreeEntry
 }
@@ -29,30 +75,150 @@ impl NodeTree {
 		}
 	}
 
-	pub fn instantiate(&self, scene: SharedSceneGraph, parent: Option<NodeId>) -> u32 {
-		let mut scene = scene.write().unwrap();
+	/// A copy of the node and everything below it. Prefab instances keep their link. Fails if any of the
+	/// components can't be cloned, see `NodeComponent::clone_component`.
+	pub fn from_scene(scene: &SceneGraph, root: NodeId) -> Result<Self, SceneError> {
+		Ok(Self {
+			root: entry_from_scene(scene, root)?
+		})
+	}
+
+	pub fn instantiate(&self, scene: SharedSceneGraph, parent: Option<NodeId>) -> NodeId {
+		Self::instantiate_entry(&self.root, &scene, parent)
+	}
 
-		fn create_node(entry: &NodeTreeEntry) -> Node {
-			let mut node = Node::new();
+	pub(crate) fn instantiate_entry(entry: &NodeTreeEntry, scene: &SharedSceneGraph, parent: Option<NodeId>) -> NodeId {
+		add_entry(&mut scene.write().unwrap(), entry, parent)
+	}
+}
 
-			for component in &entry.components {
-				node.add_component((*component).clone_component());
-			}
-			
-			node
+impl SceneGraph {
+	/// Copies the node and everything below it, see `NodeTree::from_scene`. The copy is added right after it.
+	pub fn duplicate(&mut self, id: NodeId) -> Result<NodeId, SceneError> {
+		if id == self.root_id() {
+			return Err(SceneError::new(ErrorKind::Other, "The scene root can't be duplicated"));
 		}
 
-		fn add_node(scene: &mut RwLockWriteGuard<'_, SceneGraph>, entry: &NodeTreeEntry, parent: Option<NodeId>) -> u32 {
-			let node = create_node(entry);
-			let node = scene.add_node(node, parent);
+		let tree = NodeTree::from_scene(self, id)?;
+		let index = self.index_in_parent(id).unwrap();
 
-			for child in &entry.children {
-				add_node(scene, child, Some(node));
-			}
+		Ok(insert_entry(self, &tree.root, Some(self.parent(id)), index + 1))
+	}
+}
+
+fn entry_from_scene(scene: &SceneGraph, id: NodeId) -> Result<NodeTreeEntry, SceneError> {
+	let Some(node) = scene.node(id) else {
+		return Err(SceneError::new(ErrorKind::Other, &format!("No node {} in the scene", id)));
+	};
 
-			node
+	let mut entry = NodeTreeEntry::with_name(node.name());
+	entry.groups = node.groups().to_vec();
+	entry.process = node.process_settings();
+
+	for component in node.components() {
+		// added back from the link
+		if let Some(instance) = component.as_any().downcast_ref::<PrefabInstance>() {
+			entry.prefab = Some(instance.link().clone());
+			continue;
 		}
 
-		add_node(&mut scene, &self.root, parent)
+		let Some(clone) = copy_out_of_scene(component.as_ref()) else {
+			return Err(SceneError::new(
+				ErrorKind::UncloneableComponent,
+				&format!("{} of {:?} can't be cloned", component.name(), node)
+			));
+		};
+
+		entry.components.push(clone);
+	}
+
+	for child in scene.children_slice(id) {
+		entry.children.push(entry_from_scene(scene, *child)?);
 	}
+
+	Ok(entry)
+}
+
+/// Clones a component of a node in the scene. The clone isn't in the scene itself, so it's taken out of it right
+/// away instead of keeping the original's owner and scene.
+pub(crate) fn copy_out_of_scene(component: &dyn NodeComponent) -> Option<Box<dyn NodeComponent>> {
+	let mut clone = component.clone_component()?;
+	clone.exit_scene();
+	Some(clone)
+}
+
+/// Components that can't be cloned are left out.
+pub(crate) fn clone_components(components: &[Box<dyn NodeComponent>]) -> Vec<Box<dyn NodeComponent>> {
+	components.iter()
+		.filter_map(|component| {
+			let clone = component.clone_component();
+
+			if clone.is_none() {
+				log::warn!("{} can't be cloned, leaving it out", component.name());
+			}
+
+			clone
+		})
+		.collect()
+}
+
+fn create_node(entry: &NodeTreeEntry) -> Node {
+	let mut node = match &entry.name {
+		Some(name) => Node::with_name(name),
+		None => Node::new()
+	};
+
+	for group in &entry.groups {
+		node.add_to_group(group);
+	}
+
+	node.set_process_settings(entry.process);
+	add_components(&mut node, entry);
+	node
+}
+
+fn add_components(node: &mut Node, entry: &NodeTreeEntry) {
+	for component in clone_components(&entry.components) {
+		node.add_component(component);
+	}
+
+	if let Some(link) = &entry.prefab {
+		node.add_component(Box::new(PrefabInstance::new(link.clone())));
+	}
+}
+
+/// Adds the children of the entry below `id`, then applies its prefab overrides.
+fn add_children(scene: &mut SceneGraph, entry: &NodeTreeEntry, id: NodeId) {
+	for child in &entry.children {
+		add_entry(scene, child, Some(id));
+	}
+
+	// after the children, so overrides can reach into them
+	if let Some(link) = &entry.prefab {
+		link.apply(scene, id);
+	}
+}
+
+fn add_entry(scene: &mut SceneGraph, entry: &NodeTreeEntry, parent: Option<NodeId>) -> NodeId {
+	insert_entry(scene, entry, parent, usize::MAX)
+}
+
+/// Same as `add_entry`, but puts the entry at `index` among its siblings, see `SceneGraph::insert_node_at`.
+pub(crate) fn insert_entry(scene: &mut SceneGraph, entry: &NodeTreeEntry, parent: Option<NodeId>, index: usize) -> NodeId {
+	let node = scene.insert_node_at(create_node(entry), parent, index);
+	add_children(scene, entry, node);
+	node
+}
+
+/// Replaces the components and children of the node with the entry's, keeping its name and groups.
+pub(crate) fn rebuild_entry(scene: &mut SceneGraph, entry: &NodeTreeEntry, id: NodeId) {
+	for child in scene.children(id) {
+		scene.remove_node(child);
+	}
+
+	let node = scene.node_mut(id).unwrap();
+	node.clear_components();
+	add_components(node, entry);
+
+	add_children(scene, entry, id);
 }
@@ -0,0 +1,21 @@
+use fatum_scene::{NodeComponent, NodeId, Reflect, SharedSceneGraph};
+use serde::{Deserialize, Serialize};
+
+#[derive(NodeComponent, Reflect, Clone, Serialize, Deserialize)]
+#[component(serialize, reflect)]
+pub struct Health {
+	#[serde(skip)]
+	#[reflect(skip)]
+	owner: NodeId,
+	#[serde(skip)]
+	#[reflect(skip)]
+	scene: Option<SharedSceneGraph>,
+
+	pub current: f32,
+	pub max: f32
+}
+
+/// At full health.
+pub fn health(current: f32) -> Box<Health> {
+	Box::new(Health { owner: NodeId::INVALID, scene: None, current, max: current })
+}
@@ -0,0 +1,43 @@
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use fatum_scene::{Node, SceneGraph};
+use fatum_signals::Executor;
+
+#[test]
+fn cancelled_on_exit() {
+	let scene = SceneGraph::new();
+	let ticks = Rc::new(Cell::new(0));
+
+	let id = scene.write().unwrap().add_node(Node::with_name("Blinker"), None);
+
+	let task = {
+		let mut graph = scene.write().unwrap();
+		let node = graph.node_mut(id).unwrap();
+
+		let ready = node.next_key(Node::READY);
+		let ticks = ticks.clone();
+
+		node.spawn(async move {
+			ready.await;
+
+			loop {
+				ticks.set(ticks.get() + 1);
+				Executor::next_frame().await;
+			}
+		})
+	};
+
+	Executor::update(Duration::from_millis(16));
+	assert_eq!(ticks.get(), 0);
+
+	scene.read().unwrap().node(id).unwrap().ready();
+	Executor::update(Duration::from_millis(16));
+	Executor::update(Duration::from_millis(16));
+	assert_eq!(ticks.get(), 2);
+
+	scene.write().unwrap().node_mut(id).unwrap().exit_scene();
+	assert!(task.is_cancelled());
+
+	Executor::update(Duration::from_millis(16));
+	assert_eq!(ticks.get(), 2);
+}
@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use fatum_scene::{Node, SceneGraph};
+use fatum_signals::SignalQueue;
+
+#[test]
+fn deferred_node_signal() {
+	let scene = SceneGraph::new();
+	let handler_scene = Arc::downgrade(&scene);
+
+	let spawner = {
+		let mut graph = scene.write().unwrap();
+		let spawner = graph.add_node(Node::with_name("Spawner"), None);
+
+		let node = graph.node_mut(spawner).unwrap();
+		node.create_signal::<u32>("spawn");
+
+		node.connect("spawn", move |args: &(*const Node, u32)| {
+			// a synchronous emit would deadlock here, the write lock below is still held
+			let scene = handler_scene.upgrade().unwrap();
+			let mut graph = scene.write().unwrap();
+			let parent = unsafe { &*args.0 }.id();
+
+			for i in 0..args.1 {
+				graph.add_node(Node::with_name(&format!("Spawned{}", i)), Some(parent));
+			}
+		});
+
+		graph.node(spawner).unwrap().emit_deferred("spawn", 2u32);
+		spawner
+	};
+
+	assert!(scene.read().unwrap().children(spawner).is_empty());
+
+	SignalQueue::flush();
+	assert_eq!(scene.read().unwrap().children(spawner).len(), 2);
+}
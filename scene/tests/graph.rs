@@ -0,0 +1,128 @@
+use std::{cell::RefCell, rc::Rc};
+
+use fatum_scene::{Node, NodeId, SceneGraph};
+
+#[test]
+fn removed_ids_stay_stale() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+
+	let enemy = graph.add_node(Node::with_name("Enemy"), None);
+	assert!(graph.remove_node(enemy));
+	assert!(!graph.contains(enemy));
+	assert!(!graph.remove_node(enemy));
+
+	// the slot is reused, the old id still doesn't resolve
+	let next = graph.add_node(Node::with_name("NextEnemy"), None);
+	assert_eq!(next.index(), enemy.index());
+	assert_ne!(next, enemy);
+	assert!(graph.node(enemy).is_none());
+	assert_eq!(graph.node(next).unwrap().name(), "NextEnemy");
+}
+
+#[test]
+fn remove_subtree() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+
+	let root = graph.root_id();
+	let level = graph.add_node(Node::with_name("Level"), None);
+	let enemy = graph.add_node(Node::with_name("Enemy"), Some(level));
+	let weapon = graph.add_node(Node::with_name("Weapon"), Some(enemy));
+	let other = graph.add_node(Node::with_name("Other"), Some(level));
+
+	assert!(graph.remove_node(enemy));
+	assert!(!graph.contains(weapon));
+	assert_eq!(graph.children(level), vec![other]);
+	assert!(graph.children(enemy).is_empty());
+	assert_eq!(graph.node_count(), 3);
+
+	// long-running levels keep spawning and killing
+	for i in 0..100 {
+		let spawned = graph.add_node(Node::with_name(&format!("Spawned{}", i)), Some(level));
+		graph.add_node(Node::with_name("Child"), Some(spawned));
+		assert!(graph.remove_node(spawned));
+	}
+
+	assert_eq!(graph.children(level), vec![other]);
+	assert_eq!(graph.node_count(), 3);
+
+	assert!(!graph.remove_node(root));
+	assert!(graph.contains(root));
+}
+
+#[test]
+fn removed_signal() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+
+	let enemy = graph.add_node(Node::with_name("Enemy"), None);
+	let weapon = graph.add_node(Node::with_name("Weapon"), Some(enemy));
+
+	let removed: Rc<RefCell<Vec<(NodeId, usize)>>> = Rc::default();
+	let handler = removed.clone();
+	graph.node_removed.connect(move |args| {
+		let (scene, id) = unsafe { (&*args.0, (*args.1).id()) };
+		// still in its parent, without its children
+		assert!(scene.children_slice(id).is_empty());
+		handler.borrow_mut().push((id, scene.index_in_parent(id).unwrap()));
+	});
+
+	assert!(graph.remove_node(enemy));
+	// children first, ids still set
+	assert_eq!(*removed.borrow(), vec![(weapon, 0), (enemy, 0)]);
+	assert!(graph.children_slice(graph.root_id()).is_empty());
+}
+
+#[test]
+fn reparent() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+
+	let moved: Rc<RefCell<Vec<(NodeId, NodeId, usize)>>> = Rc::default();
+	let log = moved.clone();
+	graph.node_moved.connect(move |args| {
+		log.borrow_mut().push((unsafe { &*args.1 }.id(), args.2, args.3));
+	});
+
+	let root = graph.root_id();
+	let player = graph.add_node(Node::with_name("Player"), None);
+	let hand = graph.add_node(Node::with_name("Hand"), Some(player));
+	let sword = graph.add_node(Node::with_name("Sword"), None);
+	let gem = graph.add_node(Node::with_name("Gem"), Some(sword));
+
+	assert!(graph.reparent(sword, hand));
+	assert_eq!(graph.parent(sword), hand);
+	assert_eq!(graph.children(root), vec![player]);
+	assert_eq!(graph.parent(gem), sword);
+	assert_eq!(moved.borrow().as_slice(), &[(sword, root, 1)]);
+
+	// no cycles
+	assert!(!graph.reparent(player, gem));
+	assert!(!graph.reparent(hand, hand));
+	assert!(!graph.reparent(root, player));
+	assert_eq!(moved.borrow().len(), 1);
+}
+
+#[test]
+fn sibling_order() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+
+	let menu = graph.add_node(Node::with_name("Menu"), None);
+	let play = graph.add_node(Node::with_name("Play"), Some(menu));
+	let quit = graph.add_node(Node::with_name("Quit"), Some(menu));
+	let options = graph.insert_node_at(Node::with_name("Options"), Some(menu), 1);
+	let credits = graph.insert_node_at(Node::with_name("Credits"), Some(menu), 100);
+
+	assert_eq!(graph.children(menu), vec![play, options, quit, credits]);
+	assert_eq!(graph.index_in_parent(quit), Some(2));
+
+	assert!(graph.move_child(menu, 3, 0));
+	assert_eq!(graph.children(menu), vec![credits, play, options, quit]);
+	assert!(!graph.move_child(menu, 4, 0));
+
+	assert!(graph.swap_siblings(credits, quit));
+	assert_eq!(graph.children(menu), vec![quit, play, options, credits]);
+	assert!(!graph.swap_siblings(menu, quit));
+}
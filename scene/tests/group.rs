@@ -0,0 +1,126 @@
+use std::{cell::RefCell, rc::Rc};
+
+use fatum_scene::{ComponentRegistry, Node, NodeId, SceneGraph};
+
+fn tagged(name: &str, groups: &[&str]) -> Node {
+	let mut node = Node::with_name(name);
+
+	for group in groups {
+		node.add_to_group(group);
+	}
+
+	node
+}
+
+#[test]
+fn membership() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+
+	let level = graph.add_node(Node::with_name("Level"), None);
+	let goblin = graph.add_node(tagged("Goblin", &["enemies", "persist"]), Some(level));
+	let orc = graph.add_node(tagged("Orc", &["enemies"]), Some(level));
+	let chest = graph.add_node(tagged("Chest", &["interactable", "persist"]), Some(level));
+
+	assert_eq!(graph.nodes_in_group("enemies"), vec![goblin, orc]);
+	assert_eq!(graph.nodes_in_group("persist"), vec![goblin, chest]);
+	assert!(graph.nodes_in_group("allies").is_empty());
+
+	// through the node, the index catches up on the next query
+	assert!(graph.node_mut(chest).unwrap().add_to_group("enemies"));
+	assert!(!graph.node_mut(chest).unwrap().add_to_group("enemies"));
+	graph.node_mut(goblin).unwrap().remove_from_group("enemies");
+	assert_eq!(graph.nodes_in_group("enemies"), vec![orc, chest]);
+
+	graph.remove_node(orc);
+	assert_eq!(graph.nodes_in_group("enemies"), vec![chest]);
+
+	graph.remove_node(level);
+	assert_eq!(graph.group_size("enemies"), 0);
+	assert_eq!(graph.group_size("persist"), 0);
+}
+
+#[test]
+fn signals() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+
+	let events: Rc<RefCell<Vec<(bool, NodeId, String)>>> = Rc::default();
+
+	let log = events.clone();
+	graph.node_added_to_group.connect(move |args| {
+		log.borrow_mut().push((true, unsafe { &*args.1 }.id(), args.2.clone()));
+	});
+
+	let log = events.clone();
+	graph.node_removed_from_group.connect(move |args| {
+		log.borrow_mut().push((false, unsafe { &*args.1 }.id(), args.2.clone()));
+	});
+
+	let goblin = graph.add_node(tagged("Goblin", &["enemies"]), None);
+	assert!(graph.add_to_group(goblin, "persist"));
+	assert!(graph.remove_from_group(goblin, "enemies"));
+
+	// through the node, seen once the indices catch up
+	assert!(graph.node_mut(goblin).unwrap().add_to_group("bosses"));
+	assert_eq!(events.borrow().len(), 3);
+	graph.update_indices();
+	assert_eq!(events.borrow().len(), 4);
+
+	graph.remove_node(goblin);
+
+	assert_eq!(*events.borrow(), vec![
+		(true, goblin, "enemies".to_string()),
+		(true, goblin, "persist".to_string()),
+		(false, goblin, "enemies".to_string()),
+		(true, goblin, "bosses".to_string()),
+		(false, goblin, "persist".to_string()),
+		(false, goblin, "bosses".to_string())
+	]);
+}
+
+#[test]
+fn bulk() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+
+	let goblin = graph.add_node(tagged("Goblin", &["enemies"]), None);
+	let orc = graph.add_node(tagged("Orc", &["enemies"]), None);
+	graph.add_node(Node::with_name("Player"), None);
+
+	graph.call_group("enemies", |node| {
+		let name = format!("Dead{}", node.name());
+		node.set_name(&name);
+		node.remove_from_group("enemies");
+	});
+
+	assert_eq!(graph.node(goblin).unwrap().name(), "DeadGoblin");
+	assert_eq!(graph.node(orc).unwrap().name(), "DeadOrc");
+	assert!(graph.nodes_in_group("enemies").is_empty());
+
+	let alerted: Rc<RefCell<Vec<u32>>> = Rc::default();
+	for id in [goblin, orc] {
+		let log = alerted.clone();
+		let node = graph.node_mut(id).unwrap();
+
+		node.add_to_group("alert");
+		node.create_signal::<u32>("alarm");
+		node.connect::<u32, _>("alarm", move |args| log.borrow_mut().push(args.1));
+	}
+
+	graph.emit_group("alert", "alarm", 3u32);
+	assert_eq!(*alerted.borrow(), vec![3, 3]);
+}
+
+#[test]
+fn saved() {
+	let scene = SceneGraph::new();
+	scene.write().unwrap().add_node(tagged("Chest", &["interactable", "persist"]), None);
+
+	let ron = scene.read().unwrap().to_ron(&ComponentRegistry::new()).unwrap();
+	let loaded = SceneGraph::from_ron(&ron, &ComponentRegistry::new()).unwrap();
+	let graph = loaded.read().unwrap();
+
+	let chest = graph.nodes_in_group("persist")[0];
+	assert_eq!(graph.node(chest).unwrap().groups(), ["interactable", "persist"]);
+}
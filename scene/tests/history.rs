@@ -0,0 +1,176 @@
+mod common;
+
+use common::{Health, health};
+use fatum_scene::{History, Node, NodeId, SceneGraph, reflect::Value};
+
+fn current(graph: &SceneGraph, id: NodeId) -> Option<f32> {
+	Some(graph.node(id)?.component::<Health>()?.current)
+}
+
+#[test]
+fn nodes() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+	let mut history = History::new();
+
+	let knight = history.add_node(&mut graph, Node::with_name("Knight"), None);
+	let sword = history.add_node(&mut graph, Node::with_name("Sword"), Some(knight));
+	let squire = history.add_node(&mut graph, Node::with_name("Squire"), None);
+	graph.node_mut(sword).unwrap().add_component(health(3.0));
+
+	assert!(history.remove_node(&mut graph, knight));
+	assert!(!graph.contains(knight));
+
+	// back where it was, children and components included
+	assert!(history.undo(&mut graph));
+	let knight = history.resolve(knight);
+	let sword = history.resolve(sword);
+	assert_eq!(graph.children(graph.root_id()), vec![knight, squire]);
+	assert_eq!(graph.children(knight), vec![sword]);
+	assert_eq!(current(&graph, sword), Some(3.0));
+
+	assert!(history.undo(&mut graph));
+	assert!(history.undo(&mut graph));
+	assert!(!graph.contains(squire));
+	assert!(!graph.contains(history.resolve(sword)));
+
+	assert!(history.redo(&mut graph));
+	assert!(history.redo(&mut graph));
+	assert_eq!(graph.get_node("Knight/Sword"), Some(history.resolve(sword)));
+
+	// a new edit drops what could be redone
+	assert!(history.can_redo());
+	history.add_node(&mut graph, Node::with_name("Horse"), None);
+	assert!(!history.can_redo());
+}
+
+#[test]
+fn components_and_moves() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+	let mut history = History::new();
+
+	let knight = graph.add_node(Node::with_name("Knight"), None);
+	let squire = graph.add_node(Node::with_name("Squire"), None);
+	let horse = graph.add_node(Node::with_name("Horse"), None);
+
+	assert!(history.add_component(&mut graph, knight, health(10.0)));
+	assert!(!history.add_component(&mut graph, knight, health(5.0)));
+	assert!(history.remove_component::<Health>(&mut graph, knight));
+	assert!(!history.remove_component::<Health>(&mut graph, knight));
+
+	assert!(history.undo(&mut graph));
+	assert_eq!(current(&graph, knight), Some(10.0));
+	assert!(history.undo(&mut graph));
+	assert!(!graph.node(knight).unwrap().has_component::<Health>());
+	assert!(!history.can_undo());
+
+	assert!(history.reparent(&mut graph, knight, horse));
+	assert_eq!(graph.parent(knight), horse);
+
+	assert!(history.undo(&mut graph));
+	assert_eq!(graph.children(graph.root_id()), vec![knight, squire, horse]);
+}
+
+#[test]
+fn merged_field_changes() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+	let mut history = History::new();
+
+	let mut knight = Node::with_name("Knight");
+	knight.add_component(health(10.0));
+	let knight = graph.add_node(knight, None);
+
+	// a drag, one change per frame
+	for frame in 1..=5 {
+		assert!(history.set_field::<Health>(&mut graph, knight, "current", Value::F32(10.0 + frame as f32)));
+	}
+	history.seal();
+	assert!(history.set_field::<Health>(&mut graph, knight, "current", Value::F32(50.0)));
+	assert!(!history.set_field::<Health>(&mut graph, knight, "missing", Value::F32(0.0)));
+
+	assert!(history.undo(&mut graph));
+	assert_eq!(current(&graph, knight), Some(15.0));
+	assert!(history.undo(&mut graph));
+	assert_eq!(current(&graph, knight), Some(10.0));
+	assert!(!history.can_undo());
+
+	assert!(history.redo(&mut graph));
+	assert_eq!(current(&graph, knight), Some(15.0));
+}
+
+#[test]
+fn outside_changes() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+	let mut history = History::with_limit(2);
+
+	let knight = history.add_node(&mut graph, Node::with_name("Knight"), None);
+	history.add_component(&mut graph, knight, health(1.0));
+	history.set_field::<Health>(&mut graph, knight, "current", Value::F32(2.0));
+
+	// removed behind the history's back
+	graph.remove_node(knight);
+
+	assert!(!history.undo(&mut graph));
+	assert!(!history.undo(&mut graph));
+	// only two steps were kept
+	assert!(!history.can_undo());
+}
+
+#[test]
+fn watched_scene() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+	let mut history = History::new();
+	history.watch(&mut graph);
+
+	let root = graph.root_id();
+	let squire = graph.add_node(Node::with_name("Squire"), None);
+	let knight = graph.add_node(Node::with_name("Knight"), None);
+	// part of adding the knight
+	let sword = graph.add_node(Node::with_name("Sword"), Some(knight));
+	graph.node_mut(sword).unwrap().add_component(health(3.0));
+
+	graph.node_mut(squire).unwrap().add_component(health(5.0));
+	assert!(graph.move_child(root, 1, 0));
+	assert!(graph.remove_node(knight));
+
+	// the removed knight comes back with its sword
+	assert!(history.undo(&mut graph));
+	let knight = history.resolve(knight);
+	let sword = history.resolve(sword);
+	assert_eq!(graph.children(root), vec![knight, squire]);
+	assert_eq!(current(&graph, sword), Some(3.0));
+
+	assert!(history.undo(&mut graph));
+	assert_eq!(graph.children(root), vec![squire, knight]);
+	assert!(history.undo(&mut graph));
+	assert!(!graph.node(squire).unwrap().has_component::<Health>());
+	assert!(history.undo(&mut graph));
+	assert!(!graph.contains(knight) && !graph.contains(sword));
+
+	// what the history does itself isn't recorded twice
+	assert!(history.redo(&mut graph));
+	assert!(graph.get_node("Knight/Sword").is_some());
+	assert!(history.can_redo());
+
+	// changes made since drop what could be redone, and can be undone along with the history's own
+	graph.node_mut(squire).unwrap().add_component(health(7.0));
+	assert!(!history.can_redo());
+	history.add_node(&mut graph, Node::with_name("Horse"), Some(squire));
+
+	assert!(history.undo(&mut graph));
+	assert!(graph.children(squire).is_empty());
+	assert!(history.undo(&mut graph));
+	assert!(!graph.node(squire).unwrap().has_component::<Health>());
+
+	history.unwatch();
+	graph.remove_node(squire);
+	assert!(history.undo(&mut graph));
+	assert!(!graph.contains(history.resolve(knight)));
+	// the squire's removal wasn't recorded
+	assert!(!history.undo(&mut graph));
+	assert!(!history.can_undo());
+}
@@ -0,0 +1,92 @@
+use fatum_scene::{Node, SceneGraph, matches_pattern};
+
+#[test]
+fn patterns() {
+	assert!(matches_pattern("*", "Goblin"));
+	assert!(matches_pattern("Gob*", "Goblin"));
+	assert!(matches_pattern("*lin", "Goblin"));
+	assert!(matches_pattern("G?bl*n", "Goblin"));
+	assert!(matches_pattern("*o*i*", "Goblin"));
+	assert!(!matches_pattern("Orc*", "Goblin"));
+	assert!(!matches_pattern("Goblin?", "Goblin"));
+}
+
+#[test]
+fn paths() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+
+	let level = graph.add_node(Node::with_name("Level"), None);
+	let player = graph.add_node(Node::with_name("Player"), Some(level));
+	let camera = graph.add_node(Node::with_name("Camera"), Some(player));
+	let weapon = graph.add_node(Node::with_name("Weapon"), Some(player));
+	let enemies = graph.add_node(Node::with_name("Enemies"), Some(level));
+	let goblin = graph.add_node(Node::with_name("Goblin"), Some(enemies));
+	let orc = graph.add_node(Node::with_name("Orc"), Some(enemies));
+
+	assert_eq!(graph.get_node("Level/Player/Camera"), Some(camera));
+	assert_eq!(graph.get_node("/Level/Player"), Some(player));
+	assert_eq!(graph.get_node_from(camera, "../Weapon"), Some(weapon));
+	assert_eq!(graph.get_node_from(camera, "/Level/Enemies/./Orc"), Some(orc));
+	assert_eq!(graph.get_node_from(camera, "../../.."), Some(graph.root_id()));
+	assert_eq!(graph.get_node("Level/Nobody"), None);
+	assert_eq!(graph.get_node(".."), None);
+
+	assert_eq!(graph.get_nodes("Level/Enemies/*"), vec![goblin, orc]);
+	assert_eq!(graph.get_nodes("Level/*/C*"), vec![camera]);
+	assert_eq!(graph.get_nodes_from(goblin, "../*/.."), vec![enemies]);
+
+	assert_eq!(graph.path_of(weapon).as_deref(), Some("/Level/Player/Weapon"));
+}
+
+#[test]
+fn duplicate_names() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+
+	let left = graph.add_node(Node::with_name("Squad"), None);
+	let right = graph.add_node(Node::with_name("Squad"), None);
+	let first = graph.add_node(Node::with_name("Orc"), Some(left));
+	let other = graph.add_node(Node::with_name("Orc"), Some(right));
+	let second = graph.add_node(Node::with_name("Orc"), Some(left));
+
+	assert_eq!(graph.get_nodes("Squad/Orc"), vec![first, second, other]);
+	assert_eq!(graph.get_nodes("Squad/Orc/.."), vec![left, right]);
+
+	// tree order, not the order they were added in
+	graph.move_child(left, 1, 0);
+	assert_eq!(graph.get_nodes("Squad/Orc"), vec![second, first, other]);
+	assert_eq!(graph.get_node_from(right, "Orc"), Some(other));
+}
+
+#[test]
+fn renamed_nodes() {
+	let scene = SceneGraph::new();
+
+	let (player, hud) = {
+		let mut graph = scene.write().unwrap();
+
+		let player = graph.add_node(Node::with_name("Player"), None);
+		let hud = graph.add_node(Node::with_name("Hud"), Some(player));
+		assert_eq!(graph.node_by_name("Player").unwrap().id(), player);
+
+		graph.node_mut(player).unwrap().set_name("Hero");
+		assert!(graph.node_by_name("Player").is_none());
+		assert_eq!(graph.node_by_name("Hero").unwrap().id(), player);
+		assert_eq!(graph.get_node("Hero/Hud"), Some(hud));
+
+		(player, hud)
+	};
+
+	{
+		// Node::get_node takes its own read lock, fine next to another reader
+		let graph = scene.read().unwrap();
+		assert_eq!(graph.node(hud).unwrap().get_node(".."), Some(player));
+		assert_eq!(graph.node(hud).unwrap().path().as_deref(), Some("/Hero/Hud"));
+	}
+
+	let mut graph = scene.write().unwrap();
+	graph.remove_node(player);
+	assert!(graph.node_by_name("Hero").is_none());
+	assert!(graph.nodes_by_name("Hud").is_empty());
+}
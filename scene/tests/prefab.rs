@@ -0,0 +1,215 @@
+use std::{cell::Cell, path::PathBuf, rc::Rc};
+
+mod common;
+
+use common::{Health, health};
+use fatum_scene::{ComponentRegistry, NodeComponent, NodeId, NodeTree, NodeTreeEntry, PrefabInstance, PrefabLibrary, PrefabLink, PrefabOverride, SceneGraph, SharedSceneGraph, reflect::Value};
+use serde::{Deserialize, Serialize};
+
+#[derive(NodeComponent, Clone, Serialize, Deserialize)]
+#[component(serialize)]
+struct Weapon {
+	#[serde(skip)]
+	owner: NodeId,
+	#[serde(skip)]
+	scene: Option<SharedSceneGraph>,
+
+	kind: String
+}
+
+#[derive(NodeComponent)]
+#[component(no_clone)]
+struct Callback {
+	owner: NodeId,
+	scene: Option<SharedSceneGraph>,
+
+	#[allow(dead_code)]
+	f: Box<dyn Fn()>
+}
+
+/// Counts its copies in a scene.
+#[derive(Clone)]
+struct Torch {
+	lit: Rc<Cell<u32>>
+}
+
+impl NodeComponent for Torch {
+	fn name(&self) -> &str { "Torch" }
+
+	fn enter_scene(&mut self, _owner: NodeId, _scene: SharedSceneGraph) {
+		self.lit.set(self.lit.get() + 1);
+	}
+
+	fn exit_scene(&mut self) {
+		self.lit.set(self.lit.get() - 1);
+	}
+
+	fn clone_component(&self) -> Option<Box<dyn NodeComponent>> { Some(Box::new(self.clone())) }
+	fn as_any(&self) -> &dyn std::any::Any { self }
+	fn as_any_mut(&mut self) -> &mut dyn std::any::Any { self }
+}
+
+fn weapon(kind: &str) -> Box<Weapon> {
+	Box::new(Weapon { owner: NodeId::INVALID, scene: None, kind: kind.to_string() })
+}
+
+fn library() -> (PrefabLibrary, PathBuf) {
+	let mut registry = ComponentRegistry::new();
+	registry.register::<Health>();
+	registry.register::<Weapon>();
+
+	let directory = std::env::temp_dir().join(format!("fatum_prefabs_{}_{:?}", std::process::id(), std::thread::current().id()));
+	std::fs::create_dir_all(&directory).unwrap();
+
+	(PrefabLibrary::new(&directory, registry), directory)
+}
+
+fn goblin(hand: &str) -> NodeTree {
+	let mut tree = NodeTree::new();
+	tree.root.name = Some("Goblin".into());
+	tree.root.groups.push("enemies".into());
+	tree.root.components.push(health(10.0));
+
+	let mut hand_entry = NodeTreeEntry::with_name(hand);
+	hand_entry.components.push(weapon("Club"));
+	tree.root.children.push(hand_entry);
+
+	tree
+}
+
+fn camp() -> NodeTree {
+	let mut tree = NodeTree::new();
+	tree.root.name = Some("Camp".into());
+
+	let mut guard = NodeTreeEntry::instance("goblin.scene");
+	guard.name = Some("Guard".into());
+	guard.prefab = Some(PrefabLink::new("goblin.scene")
+		.with_override(PrefabOverride::field::<Health>(".", "current", Value::F32(20.0))));
+
+	tree.root.children.push(guard);
+	tree.root.children.push(NodeTreeEntry::with_name("Fire"));
+	tree
+}
+
+#[test]
+fn instances() {
+	let (mut library, directory) = library();
+	goblin("Hand").save(directory.join("goblin.scene"), library.registry()).unwrap();
+	library.insert("camp.scene", camp());
+
+	let scene = SceneGraph::new();
+	let goblin = library.instantiate("goblin.scene", vec![
+		PrefabOverride::field::<Health>(".", "current", Value::F32(3.0)),
+		PrefabOverride::component("Hand", weapon("Spear")).unwrap()
+	], &scene, None).unwrap();
+	let camp = library.instantiate("camp.scene", Vec::new(), &scene, None).unwrap();
+
+	let graph = scene.read().unwrap();
+	assert_eq!(graph.node(goblin).unwrap().component::<Health>().unwrap().current, 3.0);
+	assert_eq!(graph.node(goblin).unwrap().component::<PrefabInstance>().unwrap().prefab(), PathBuf::from("goblin.scene"));
+
+	let spear = graph.get_node_from(goblin, "Hand").unwrap();
+	assert_eq!(graph.node(spear).unwrap().component::<Weapon>().unwrap().kind, "Spear");
+
+	// nested in the camp, with the camp's override
+	let guard = graph.get_node_from(camp, "Guard").unwrap();
+	assert_eq!(graph.node(guard).unwrap().component::<Health>().unwrap().current, 20.0);
+	assert!(graph.get_node_from(camp, "Guard/Hand").is_some());
+	assert_eq!(graph.nodes_in_group("enemies"), vec![goblin, guard]);
+
+	std::fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn saved_as_links() {
+	let (mut library, directory) = library();
+	library.insert("goblin.scene", goblin("Hand"));
+
+	let scene = SceneGraph::new();
+	library.instantiate("goblin.scene", vec![PrefabOverride::component("Hand", weapon("Axe")).unwrap()], &scene, None).unwrap();
+
+	let ron = scene.read().unwrap().to_ron(library.registry()).unwrap();
+	assert!(ron.contains("goblin.scene"));
+	assert!(!ron.contains("Club"), "the prefab's own components shouldn't be saved with the instance");
+
+	let mut tree = NodeTree::from_ron(&ron, library.registry()).unwrap();
+	library.expand(&mut tree).unwrap();
+	let loaded = SceneGraph::from_tree(&tree);
+	let graph = loaded.read().unwrap();
+
+	let hand = graph.get_node("Goblin/Hand").unwrap();
+	assert_eq!(graph.node(hand).unwrap().component::<Weapon>().unwrap().kind, "Axe");
+
+	std::fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn refresh() {
+	let (mut library, directory) = library();
+	goblin("Hand").save(directory.join("goblin.scene"), library.registry()).unwrap();
+	library.insert("camp.scene", camp());
+
+	let scene = SceneGraph::new();
+	let goblin_id = library.instantiate("goblin.scene", Vec::new(), &scene, None).unwrap();
+	let camp_id = library.instantiate("camp.scene", Vec::new(), &scene, None).unwrap();
+
+	// the file changes under us
+	goblin("Claw").save(directory.join("goblin.scene"), library.registry()).unwrap();
+	library.reload("goblin.scene").unwrap();
+	assert_eq!(library.refresh(&scene, "goblin.scene").unwrap(), 2);
+
+	let graph = scene.read().unwrap();
+	assert!(graph.get_node_from(goblin_id, "Hand").is_none());
+	assert!(graph.get_node_from(goblin_id, "Claw").is_some());
+	assert!(graph.get_node_from(camp_id, "Guard/Claw").is_some());
+	assert!(graph.get_node_from(camp_id, "Fire").is_some());
+
+	let guard = graph.get_node_from(camp_id, "Guard").unwrap();
+	assert_eq!(graph.node(guard).unwrap().component::<Health>().unwrap().current, 20.0);
+
+	std::fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn refresh_exits_components() {
+	let (mut library, directory) = library();
+	let lit = Rc::new(Cell::new(0));
+
+	let mut torch = NodeTree::new();
+	torch.root.components.push(Box::new(Torch { lit: lit.clone() }));
+	library.insert("torch.scene", torch);
+
+	let scene = SceneGraph::new();
+	library.instantiate("torch.scene", Vec::new(), &scene, None).unwrap();
+	assert_eq!(lit.get(), 1);
+
+	// the old copy leaves the scene, the new one enters it
+	assert_eq!(library.refresh(&scene, "torch.scene").unwrap(), 1);
+	assert_eq!(lit.get(), 1);
+
+	std::fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn recursive() {
+	let (mut library, directory) = library();
+
+	let mut tree = NodeTree::new();
+	tree.root.children.push(NodeTreeEntry::instance("loop.scene"));
+	library.insert("loop.scene", tree);
+
+	assert!(library.get("loop.scene").is_err());
+
+	std::fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn component_overrides() {
+	let callback = Box::new(Callback { owner: NodeId::INVALID, scene: None, f: Box::new(|| ()) });
+	assert!(PrefabOverride::component("Hand", callback).is_none());
+
+	let r#override = PrefabOverride::component("Hand", weapon("Spear")).unwrap();
+	let PrefabOverride::Component { node, component } = r#override.clone() else { panic!() };
+	assert_eq!(node, "Hand");
+	assert_eq!(component.get().as_any().downcast_ref::<Weapon>().unwrap().kind, "Spear");
+}
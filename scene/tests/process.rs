@@ -0,0 +1,95 @@
+use fatum_scene::{ComponentRegistry, Node, NodeId, NodeTree, ProcessMode, SceneGraph};
+
+fn names(graph: &SceneGraph, ids: Vec<NodeId>) -> Vec<&str> {
+	ids.into_iter().map(|id| graph.node(id).unwrap().name()).collect()
+}
+
+#[test]
+fn enabled() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+
+	let enemies = graph.add_node(Node::with_name("Enemies"), None);
+	let goblin = graph.add_node(Node::with_name("Goblin"), Some(enemies));
+	graph.add_node(Node::with_name("Orc"), Some(enemies));
+	graph.add_node(Node::with_name("Player"), None);
+
+	assert_eq!(names(&graph, graph.process_order(false)), vec!["Enemies", "Goblin", "Orc", "Player"]);
+
+	graph.node_mut(enemies).unwrap().set_enabled(false);
+	assert_eq!(names(&graph, graph.process_order(false)), vec!["Player"]);
+	assert!(graph.node(goblin).unwrap().is_enabled());
+	assert!(!graph.is_enabled_in_tree(goblin));
+}
+
+#[test]
+fn pause_modes() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+
+	let mut menu = Node::with_name("PauseMenu");
+	menu.set_process_mode(ProcessMode::WhenPaused);
+	let menu = graph.add_node(menu, None);
+	graph.add_node(Node::with_name("Resume"), Some(menu));
+
+	let mut music = Node::with_name("Music");
+	music.set_process_mode(ProcessMode::Always);
+	graph.add_node(music, None);
+
+	let enemy = graph.add_node(Node::with_name("Enemy"), None);
+
+	assert_eq!(names(&graph, graph.process_order(false)), vec!["Music", "Enemy"]);
+	assert_eq!(names(&graph, graph.process_order(true)), vec!["PauseMenu", "Resume", "Music"]);
+	assert!(!graph.can_process(enemy, true));
+
+	// everything inherits from the root
+	let root = graph.root_id();
+	graph.node_mut(root).unwrap().set_process_mode(ProcessMode::Always);
+	assert_eq!(graph.resolved_process_mode(enemy), ProcessMode::Always);
+	assert_eq!(graph.process_order(true).len(), 4);
+}
+
+#[test]
+fn priority() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+
+	let mut camera = Node::with_name("Camera");
+	camera.set_process_priority(10);
+	graph.add_node(camera, None);
+
+	let level = graph.add_node(Node::with_name("Level"), None);
+	graph.add_node(Node::with_name("Door"), Some(level));
+
+	let mut input = Node::with_name("Input");
+	input.set_process_priority(-5);
+	graph.add_node(input, Some(level));
+
+	assert_eq!(names(&graph, graph.process_order(false)), vec!["Input", "Level", "Door", "Camera"]);
+}
+
+#[test]
+fn saved() {
+	let scene = SceneGraph::new();
+
+	{
+		let mut graph = scene.write().unwrap();
+
+		let mut menu = Node::with_name("PauseMenu");
+		menu.set_enabled(false);
+		menu.set_process_mode(ProcessMode::WhenPaused);
+		menu.set_process_priority(3);
+		graph.add_node(menu, None);
+	}
+
+	let registry = ComponentRegistry::new();
+	let ron = scene.read().unwrap().to_ron(&registry).unwrap();
+	let tree = NodeTree::from_ron(&ron, &registry).unwrap();
+	let loaded = SceneGraph::from_tree(&tree);
+
+	let graph = loaded.read().unwrap();
+	let menu = graph.node_by_name("PauseMenu").unwrap();
+	assert!(!menu.is_enabled());
+	assert_eq!(menu.process_mode(), ProcessMode::WhenPaused);
+	assert_eq!(menu.process_priority(), 3);
+}
@@ -0,0 +1,98 @@
+use fatum_scene::{Node, NodeComponent, NodeId, SceneGraph, SharedSceneGraph};
+
+#[derive(NodeComponent, Clone)]
+struct Position {
+	owner: NodeId,
+	scene: Option<SharedSceneGraph>,
+
+	x: f32
+}
+
+#[derive(NodeComponent, Clone)]
+struct Velocity {
+	owner: NodeId,
+	scene: Option<SharedSceneGraph>,
+
+	x: f32
+}
+
+fn position(x: f32) -> Box<Position> {
+	Box::new(Position { owner: NodeId::INVALID, scene: None, x })
+}
+
+fn velocity(x: f32) -> Box<Velocity> {
+	Box::new(Velocity { owner: NodeId::INVALID, scene: None, x })
+}
+
+#[test]
+fn root_components() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+	let root = graph.root_id();
+
+	graph.node_mut(root).unwrap().add_component(position(1.0));
+	let component = graph.node(root).unwrap().component::<Position>().unwrap();
+	// the root's id is a valid owner, not the "no scene" marker
+	assert_eq!(component.owner, root);
+	assert!(component.scene.is_some());
+
+	let mut copy = component.clone_component().unwrap();
+	copy.exit_scene();
+	assert_eq!(copy.as_any().downcast_ref::<Position>().unwrap().owner, NodeId::INVALID);
+}
+
+#[test]
+fn components() {
+	let mut node = Node::with_name("Ball");
+
+	assert!(node.add_component(position(1.0)));
+	assert!(node.add_component(velocity(2.0)));
+	assert!(!node.add_component(position(3.0)));
+	assert_eq!(node.components().len(), 2);
+	assert_eq!(node.component::<Position>().unwrap().x, 1.0);
+
+	assert!(node.remove_component::<Position>());
+	assert!(!node.has_component::<Position>());
+	assert_eq!(node.component::<Velocity>().unwrap().x, 2.0);
+	assert!(node.add_component(position(4.0)));
+	assert_eq!(node.component_mut::<Position>().unwrap().x, 4.0);
+}
+
+#[test]
+fn queries() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+
+	let mut ball = Node::with_name("Ball");
+	ball.add_component(position(0.0));
+	ball.add_component(velocity(2.0));
+	let ball = graph.add_node(ball, None);
+
+	let mut wall = Node::with_name("Wall");
+	wall.add_component(position(10.0));
+	let wall = graph.add_node(wall, None);
+
+	graph.add_node(Node::with_name("Empty"), None);
+
+	let mut moving: Vec<NodeId> = graph.query::<(Position, Velocity)>().map(|(id, _)| id).collect();
+	assert_eq!(moving, vec![ball]);
+
+	let mut placed: Vec<NodeId> = graph.query::<Position>().map(|(id, _)| id).collect();
+	placed.sort();
+	assert_eq!(placed, vec![ball, wall]);
+
+	graph.query_mut::<(Position, Velocity), _>(|_, (position, velocity)| {
+		position.x += velocity.x;
+	});
+	assert_eq!(graph.node(ball).unwrap().component::<Position>().unwrap().x, 2.0);
+
+	// components added and removed in the scene are picked up
+	graph.node_mut(wall).unwrap().add_component(velocity(0.0));
+	graph.node_mut(ball).unwrap().remove_component::<Velocity>();
+
+	moving = graph.query::<(Position, Velocity)>().map(|(id, _)| id).collect();
+	assert_eq!(moving, vec![wall]);
+
+	graph.remove_node(wall);
+	assert_eq!(graph.query::<Velocity>().count(), 0);
+}
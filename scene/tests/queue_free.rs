@@ -0,0 +1,71 @@
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use fatum_scene::{Node, SceneGraph};
+
+#[test]
+fn from_update() {
+	let scene = SceneGraph::new();
+	let exited: Rc<RefCell<Vec<String>>> = Rc::default();
+
+	let (bullet, trail) = {
+		let mut graph = scene.write().unwrap();
+
+		let mut bullet = Node::with_name("Bullet");
+		bullet.connect_key(Node::UPDATE, |args| unsafe { (*args.0).queue_free() });
+		let bullet = graph.add_node(bullet, None);
+		let trail = graph.add_node(Node::with_name("Trail"), Some(bullet));
+
+		for id in [bullet, trail] {
+			let exited = exited.clone();
+			graph.node_mut(id).unwrap().connect_key(Node::EXIT_TREE, move |args| {
+				exited.borrow_mut().push(unsafe { (*args.0).name().to_string() });
+			});
+		}
+
+		(bullet, trail)
+	};
+
+	{
+		let graph = scene.read().unwrap();
+
+		for id in graph.process_order(false) {
+			graph.node(id).unwrap().emit_key(Node::UPDATE, Duration::ZERO);
+		}
+
+		// still there until it's safe to remove it
+		assert!(graph.contains(bullet));
+		assert!(graph.is_queued_for_removal(bullet));
+	}
+
+	let mut graph = scene.write().unwrap();
+	assert_eq!(graph.free_queued(), 1);
+	assert!(!graph.contains(bullet));
+	assert!(!graph.contains(trail));
+	assert_eq!(*exited.borrow(), vec!["Trail", "Bullet"]);
+
+	assert_eq!(graph.free_queued(), 0);
+}
+
+#[test]
+fn queue_remove() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+
+	let chest = graph.add_node(Node::with_name("Chest"), None);
+	let coin = graph.add_node(Node::with_name("Coin"), Some(chest));
+	let gem = graph.add_node(Node::with_name("Gem"), None);
+
+	assert!(graph.queue_remove(chest));
+	assert!(graph.queue_remove(chest));
+	// goes with its parent
+	assert!(graph.queue_remove(coin));
+	assert!(graph.queue_remove(gem));
+	assert!(!graph.queue_remove(graph.root_id()));
+
+	assert_eq!(graph.free_queued(), 2);
+	assert_eq!(graph.node_count(), 1);
+	assert!(!graph.queue_remove(gem));
+
+	// not in a scene, nothing happens
+	Node::with_name("Loose").queue_free();
+}
@@ -0,0 +1,117 @@
+use fatum_scene::{Node, NodeComponent, NodeId, Reflect, SharedSceneGraph, reflect::{ReflectError, Value, ValueKind}};
+use glam::{Quat, Vec3, Vec4};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Tint(u8, u8, u8);
+
+mod tint {
+	use fatum_scene::reflect::{Value, ValueKind};
+	use glam::Vec4;
+
+	use super::Tint;
+
+	pub const KIND: ValueKind = ValueKind::Color;
+
+	pub fn to_value(tint: &Tint) -> Value {
+		Value::Color(Vec4::new(tint.0 as f32 / 255.0, tint.1 as f32 / 255.0, tint.2 as f32 / 255.0, 1.0))
+	}
+
+	pub fn from_value(value: Value) -> Option<Tint> {
+		match value {
+			Value::Color(color) => Some(Tint((color.x * 255.0) as u8, (color.y * 255.0) as u8, (color.z * 255.0) as u8)),
+			_ => None
+		}
+	}
+}
+
+#[derive(NodeComponent, Reflect, Clone)]
+#[component(reflect)]
+struct Lamp {
+	owner: NodeId,
+	scene: Option<SharedSceneGraph>,
+
+	#[reflect(set = "set_position")]
+	position: Vec3,
+	rotation: Quat,
+	intensity: f32,
+	on: bool,
+	#[reflect(with = "tint")]
+	tint: Tint,
+	#[reflect(read_only)]
+	serial: u32,
+	#[reflect(skip)]
+	dirty: bool
+}
+
+impl Lamp {
+	fn set_position(&mut self, position: Vec3) {
+		self.position = position;
+		self.dirty = true;
+	}
+}
+
+fn lamp() -> Lamp {
+	Lamp {
+		owner: NodeId::INVALID,
+		scene: None,
+		position: Vec3::ZERO,
+		rotation: Quat::IDENTITY,
+		intensity: 1.0,
+		on: true,
+		tint: Tint(255, 0, 0),
+		serial: 42,
+		dirty: false
+	}
+}
+
+#[test]
+fn fields() {
+	let lamp = lamp();
+
+	let fields: Vec<(&str, ValueKind)> = lamp.fields().iter().map(|field| (field.name, field.kind)).collect();
+	assert_eq!(fields, vec![
+		("position", ValueKind::Vec3),
+		("rotation", ValueKind::Quat),
+		("intensity", ValueKind::F32),
+		("on", ValueKind::Bool),
+		("tint", ValueKind::Color),
+		("serial", ValueKind::U32)
+	]);
+
+	assert!(lamp.field_info("serial").unwrap().read_only);
+	assert_eq!(lamp.field("intensity"), Some(Value::F32(1.0)));
+	assert_eq!(lamp.field("tint"), Some(Value::Color(Vec4::new(1.0, 0.0, 0.0, 1.0))));
+	assert_eq!(lamp.field("dirty"), None);
+}
+
+#[test]
+fn set_fields() {
+	let mut lamp = lamp();
+
+	lamp.set_field("position", Value::Vec3(Vec3::ONE)).unwrap();
+	assert_eq!(lamp.position, Vec3::ONE);
+	assert!(lamp.dirty);
+
+	lamp.set_field("tint", Value::Color(Vec4::new(0.0, 1.0, 0.0, 1.0))).unwrap();
+	assert_eq!(lamp.tint, Tint(0, 255, 0));
+
+	assert_eq!(lamp.set_field("serial", Value::U32(1)), Err(ReflectError::ReadOnly("serial")));
+	assert!(matches!(lamp.set_field("on", Value::F32(0.0)), Err(ReflectError::TypeMismatch { field: "on", .. })));
+	assert!(matches!(lamp.set_field("owner", Value::U32(0)), Err(ReflectError::NoSuchField(_))));
+}
+
+#[test]
+fn through_node() {
+	let mut node = Node::with_name("Lamp");
+	node.add_component(Box::new(lamp()));
+
+	for component in node.components() {
+		let reflect = component.reflect().unwrap();
+		assert_eq!(reflect.field("on"), Some(Value::Bool(true)));
+	}
+
+	node.component_mut::<Lamp>().unwrap()
+		.reflect_mut().unwrap()
+		.set_field("on", Value::Bool(false)).unwrap();
+	assert!(!node.component::<Lamp>().unwrap().on);
+}
@@ -0,0 +1,64 @@
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+mod common;
+
+use common::{Health, health};
+use fatum_scene::{Node, SceneGraph, Schedule, Stage, System};
+
+fn logging(name: &str, stage: Stage, log: &Rc<RefCell<Vec<String>>>) -> System {
+	let log = log.clone();
+	let name_logged = name.to_string();
+
+	System::new(name, stage, move |_, _| log.borrow_mut().push(name_logged.clone()))
+}
+
+#[test]
+fn ordering() {
+	let log: Rc<RefCell<Vec<String>>> = Rc::default();
+	let mut schedule = Schedule::new();
+
+	assert!(schedule.add(logging("physics", Stage::Update, &log)));
+	assert!(schedule.add(logging("render_prep", Stage::PreRender, &log)));
+	assert!(schedule.add(logging("ai", Stage::Update, &log).before("physics")));
+	assert!(schedule.add(logging("animation", Stage::Update, &log).after("physics").before("audio")));
+	assert!(schedule.add(logging("input", Stage::PreUpdate, &log)));
+	assert!(schedule.add(logging("audio", Stage::Update, &log)));
+
+	assert_eq!(schedule.systems(Stage::Update), vec!["ai", "physics", "animation", "audio"]);
+
+	let scene = SceneGraph::new();
+	schedule.run(&mut scene.write().unwrap(), Duration::ZERO);
+	assert_eq!(*log.borrow(), vec!["input", "ai", "physics", "animation", "audio", "render_prep"]);
+
+	// duplicates and contradictions are rejected
+	assert!(!schedule.add(logging("ai", Stage::Update, &log)));
+	assert!(!schedule.add(logging("late", Stage::Update, &log).after("audio").before("ai")));
+	assert!(!schedule.contains("late"));
+
+	assert!(schedule.remove("physics"));
+	assert_eq!(schedule.systems(Stage::Update), vec!["ai", "animation", "audio"]);
+}
+
+#[test]
+fn query_systems() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+
+	let mut knight = Node::with_name("Knight");
+	let mut wounded = health(10.0);
+	wounded.current = 5.0;
+	knight.add_component(wounded);
+	let knight = graph.add_node(knight, None);
+	graph.add_node(Node::with_name("Rock"), None);
+
+	let mut schedule = Schedule::new();
+	schedule.add(System::query::<Health, _>("regen", Stage::Update, |_, health, delta| {
+		health.current = (health.current + 2.0 * delta.as_secs_f32()).min(health.max);
+	}));
+
+	schedule.run(&mut graph, Duration::from_secs(2));
+	assert_eq!(graph.node(knight).unwrap().component::<Health>().unwrap().current, 9.0);
+
+	schedule.run(&mut graph, Duration::from_secs(2));
+	assert_eq!(graph.node(knight).unwrap().component::<Health>().unwrap().current, 10.0);
+}
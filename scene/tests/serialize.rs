@@ -0,0 +1,83 @@
+mod common;
+
+use common::{Health, health};
+use fatum_scene::{ComponentRegistry, Node, NodeComponent, NodeId, NodeTree, SceneGraph, SharedSceneGraph, error::ErrorKind};
+use serde::{Deserialize, Serialize};
+
+#[derive(NodeComponent, Clone, Serialize, Deserialize)]
+#[component(serialize = "Loot")]
+struct Drops {
+	#[serde(skip)]
+	owner: NodeId,
+	#[serde(skip)]
+	scene: Option<SharedSceneGraph>,
+
+	items: Vec<String>
+}
+
+fn registry() -> ComponentRegistry {
+	let mut registry = ComponentRegistry::new();
+	registry.register::<Health>();
+	registry.register::<Drops>();
+	registry
+}
+
+#[test]
+fn round_trip() {
+	let scene = SceneGraph::new();
+
+	{
+		let mut graph = scene.write().unwrap();
+
+		let mut goblin = Node::with_name("Goblin");
+		let mut wounded = health(10.0);
+		wounded.current = 3.0;
+		goblin.add_component(wounded);
+		goblin.add_component(Box::new(Drops { owner: NodeId::INVALID, scene: None, items: vec!["Dagger".into()] }));
+
+		let level = graph.add_node(Node::with_name("Level"), None);
+		graph.add_node(goblin, Some(level));
+		graph.add_node(Node::with_name("Door"), Some(level));
+	}
+
+	let registry = registry();
+	let ron = scene.read().unwrap().to_ron(&registry).unwrap();
+	assert!(ron.contains("Loot"));
+
+	let loaded = SceneGraph::from_ron(&ron, &registry).unwrap();
+	let graph = loaded.read().unwrap();
+
+	let level = graph.children(graph.root_id())[0];
+	assert_eq!(graph.node(level).unwrap().name(), "Level");
+
+	let children: Vec<&str> = graph.children(level).iter()
+		.map(|child| graph.node(*child).unwrap().name())
+		.collect();
+	assert_eq!(children, vec!["Goblin", "Door"]);
+
+	let goblin = graph.node(graph.children(level)[0]).unwrap();
+	let health = goblin.component::<Health>().unwrap();
+	assert_eq!((health.current, health.max), (3.0, 10.0));
+	assert_eq!(goblin.component::<Drops>().unwrap().items, vec!["Dagger".to_string()]);
+}
+
+#[test]
+fn tree_file() {
+	let path = std::env::temp_dir().join(format!("fatum_tree_{}.scene", std::process::id()));
+
+	let mut tree = NodeTree::new();
+	tree.root.name = Some("Chest".into());
+	tree.root.components.push(health(1.0));
+
+	tree.save(&path, &registry()).unwrap();
+	let loaded = NodeTree::load(&path, &registry());
+	std::fs::remove_file(&path).unwrap();
+
+	let loaded = loaded.unwrap();
+	assert_eq!(loaded.root.name.as_deref(), Some("Chest"));
+	assert_eq!(loaded.root.components.len(), 1);
+
+	// everything in the file has to be known
+	let error = NodeTree::from_ron(&tree.to_ron(&registry()).unwrap(), &ComponentRegistry::new()).err().unwrap();
+	assert!(matches!(error.kind(), ErrorKind::UnknownComponent));
+}
@@ -0,0 +1,58 @@
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use fatum_scene::{Node, NodeSignalKey, SceneGraph};
+use fatum_signals::SignalKey;
+
+const DAMAGED: NodeSignalKey<u32> = SignalKey::new("damaged");
+
+#[test]
+fn builtin_keys() {
+	let entered = Rc::new(Cell::new(false));
+	let updated = Rc::new(Cell::new(Duration::ZERO));
+
+	let mut node = Node::with_name("Player");
+
+	{
+		let entered = entered.clone();
+		node.connect_key(Node::ENTER_TREE, move |_| entered.set(true));
+	}
+
+	{
+		let updated = updated.clone();
+		node.connect_key(Node::UPDATE, move |args| updated.set(args.1));
+	}
+
+	let scene = SceneGraph::new();
+	let id = scene.write().unwrap().add_node(node, None);
+	assert!(entered.get());
+
+	scene.read().unwrap().node(id).unwrap().emit_key(Node::UPDATE, Duration::from_millis(16));
+	assert_eq!(updated.get(), Duration::from_millis(16));
+}
+
+#[test]
+fn custom_key() {
+	let health = Rc::new(Cell::new(100));
+	let mut node = Node::with_name("Enemy");
+
+	{
+		let health = health.clone();
+		node.connect_key(DAMAGED, move |args| health.set(health.get() - args.1));
+	}
+
+	node.emit_key(DAMAGED, 30);
+	assert_eq!(health.get(), 70);
+}
+
+#[test]
+#[should_panic(expected = "Signal damaged takes")]
+fn mismatched_key() {
+	const DAMAGED_F32: NodeSignalKey<f32> = SignalKey::new("damaged");
+
+	let mut node = Node::with_name("Enemy");
+	// nothing connected yet, nothing to call
+	node.emit_key(DAMAGED_F32, 1.0);
+
+	node.connect_key(DAMAGED, |_| {});
+	node.emit_key(DAMAGED_F32, 1.0);
+}
@@ -0,0 +1,110 @@
+use std::any::TypeId;
+
+mod common;
+
+use common::{Health, health};
+use fatum_scene::{Node, NodeComponent, NodeId, Reflect, SceneGraph, SceneSnapshot, SharedSceneGraph, SnapshotChange, reflect::Value};
+
+#[derive(NodeComponent, Clone)]
+struct Inventory {
+	owner: NodeId,
+	scene: Option<SharedSceneGraph>,
+
+	items: Vec<String>
+}
+
+#[derive(NodeComponent, Clone)]
+#[component(no_snapshot)]
+struct Sound {
+	owner: NodeId,
+	scene: Option<SharedSceneGraph>,
+
+	playing: bool
+}
+
+fn current(graph: &SceneGraph, id: NodeId) -> f32 {
+	graph.node(id).unwrap().component::<Health>().unwrap().current
+}
+
+fn player(graph: &mut SceneGraph) -> NodeId {
+	let mut player = Node::with_name("Player");
+	player.add_component(health(10.0));
+	player.add_component(Box::new(Inventory { owner: NodeId::INVALID, scene: None, items: vec!["Sword".to_string()] }));
+	player.add_component(Box::new(Sound { owner: NodeId::INVALID, scene: None, playing: false }));
+	graph.add_node(player, None)
+}
+
+#[test]
+fn restore() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+	let player = player(&mut graph);
+
+	let snapshot = SceneSnapshot::capture(&graph);
+	// sound is left out
+	assert_eq!(snapshot.len(), 2);
+
+	{
+		let node = graph.node_mut(player).unwrap();
+		node.component_mut::<Health>().unwrap().current = 3.0;
+		node.component_mut::<Inventory>().unwrap().items.push("Shield".to_string());
+		node.component_mut::<Sound>().unwrap().playing = true;
+	}
+	let enemy = graph.add_node(Node::with_name("Enemy"), None);
+
+	assert_eq!(snapshot.restore(&mut graph), 2);
+	let node = graph.node(player).unwrap();
+	assert_eq!(node.component::<Health>().unwrap().current, 10.0);
+	assert_eq!(node.component::<Inventory>().unwrap().items, vec!["Sword"]);
+	assert!(node.component::<Sound>().unwrap().playing);
+	// nodes added since stay
+	assert!(graph.contains(enemy));
+
+	// only the clone counts when nothing changed
+	assert_eq!(snapshot.restore(&mut graph), 1);
+
+	// removed components captured as clones come back
+	graph.node_mut(player).unwrap().remove_component::<Inventory>();
+	snapshot.restore(&mut graph);
+	assert!(graph.node(player).unwrap().has_component::<Inventory>());
+}
+
+#[test]
+fn selected_components() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+	let player = player(&mut graph);
+
+	let snapshot = SceneSnapshot::capture_components(&graph, &[TypeId::of::<Health>()]);
+	assert_eq!(snapshot.len(), 1);
+
+	graph.node_mut(player).unwrap().component_mut::<Health>().unwrap().set_field("current", Value::F32(1.0)).unwrap();
+	graph.node_mut(player).unwrap().component_mut::<Inventory>().unwrap().items.clear();
+
+	assert_eq!(snapshot.restore(&mut graph), 1);
+	assert_eq!(current(&graph, player), 10.0);
+	assert!(graph.node(player).unwrap().component::<Inventory>().unwrap().items.is_empty());
+}
+
+#[test]
+fn diff() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+	let player = player(&mut graph);
+
+	let before = SceneSnapshot::capture(&graph);
+	assert!(before.diff(&before).is_empty());
+
+	graph.node_mut(player).unwrap().component_mut::<Health>().unwrap().current = 7.5;
+	graph.node_mut(player).unwrap().remove_component::<Inventory>();
+	let mut enemy = Node::with_name("Enemy");
+	enemy.add_component(health(4.0));
+	let enemy = graph.add_node(enemy, None);
+
+	let after = SceneSnapshot::capture(&graph);
+	assert_eq!(before.diff(&after), vec![
+		SnapshotChange::Field { node: player, component: TypeId::of::<Health>(), field: "current", from: Value::F32(10.0), to: Value::F32(7.5) },
+		SnapshotChange::Added { node: enemy, component: TypeId::of::<Health>() },
+		SnapshotChange::Removed { node: player, component: TypeId::of::<Inventory>() }
+	]);
+}
@@ -0,0 +1,113 @@
+use fatum_scene::{ComponentRegistry, Node, NodeComponent, NodeId, NodeTree, PrefabLibrary, SceneGraph, SharedSceneGraph, error::ErrorKind};
+
+#[derive(NodeComponent, Clone)]
+struct Health {
+	owner: NodeId,
+	scene: Option<SharedSceneGraph>,
+
+	current: u32
+}
+
+#[derive(NodeComponent)]
+#[component(no_clone)]
+struct Callback {
+	owner: NodeId,
+	scene: Option<SharedSceneGraph>,
+
+	#[allow(dead_code)]
+	f: Box<dyn Fn()>
+}
+
+fn health(current: u32) -> Box<Health> {
+	Box::new(Health { owner: NodeId::INVALID, scene: None, current })
+}
+
+fn knight(graph: &mut SceneGraph) -> NodeId {
+	let mut knight = Node::with_name("Knight");
+	knight.add_to_group("players");
+	knight.add_component(health(5));
+	let knight = graph.add_node(knight, None);
+
+	let sword = graph.add_node(Node::with_name("Sword"), Some(knight));
+	graph.add_node(Node::with_name("Gem"), Some(sword));
+	graph.add_node(Node::with_name("Shield"), Some(knight));
+
+	knight
+}
+
+#[test]
+fn from_scene() {
+	let scene = SceneGraph::new();
+	let tree = {
+		let mut graph = scene.write().unwrap();
+		let knight = knight(&mut graph);
+		NodeTree::from_scene(&graph, knight).unwrap()
+	};
+
+	assert_eq!(tree.root.name.as_deref(), Some("Knight"));
+	assert_eq!(tree.root.groups, vec!["players"]);
+	assert_eq!(tree.root.children.iter().map(|child| child.name.as_deref().unwrap()).collect::<Vec<_>>(), vec!["Sword", "Shield"]);
+
+	// into another scene, as a copy
+	let other = SceneGraph::new();
+	let copy = tree.instantiate(other.clone(), None);
+	let graph = other.read().unwrap();
+
+	assert_eq!(graph.node(copy).unwrap().component::<Health>().unwrap().current, 5);
+	assert!(graph.get_node_from(copy, "Sword/Gem").is_some());
+	assert_eq!(graph.nodes_in_group("players"), vec![copy]);
+}
+
+#[test]
+fn duplicate() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+	let knight = knight(&mut graph);
+	let squire = graph.add_node(Node::with_name("Squire"), None);
+
+	let copy = graph.duplicate(knight).unwrap();
+	assert_eq!(graph.children(graph.root_id()), vec![knight, copy, squire]);
+
+	graph.node_mut(copy).unwrap().component_mut::<Health>().unwrap().current = 1;
+	assert_eq!(graph.node(knight).unwrap().component::<Health>().unwrap().current, 5);
+	assert_eq!(graph.descendants(copy).len(), 3);
+
+	let root = graph.root_id();
+	assert!(graph.duplicate(root).is_err());
+}
+
+#[test]
+fn uncloneable() {
+	let scene = SceneGraph::new();
+	let mut graph = scene.write().unwrap();
+	let knight = knight(&mut graph);
+
+	let gem = graph.get_node_from(knight, "Sword/Gem").unwrap();
+	graph.node_mut(gem).unwrap().add_component(Box::new(Callback { owner: NodeId::INVALID, scene: None, f: Box::new(|| ()) }));
+
+	let error = NodeTree::from_scene(&graph, knight).err().unwrap();
+	assert!(matches!(error.kind(), ErrorKind::UncloneableComponent));
+
+	// the rest of the scene can still be copied
+	assert!(NodeTree::from_scene(&graph, graph.get_node_from(knight, "Shield").unwrap()).is_ok());
+}
+
+#[test]
+fn prefab_instances() {
+	let mut library = PrefabLibrary::new(std::env::temp_dir(), ComponentRegistry::new());
+	let scene = SceneGraph::new();
+
+	let tree = {
+		let mut graph = scene.write().unwrap();
+		let knight = knight(&mut graph);
+		NodeTree::from_scene(&graph, knight).unwrap()
+	};
+	library.insert("knight.scene", tree);
+
+	let instance = library.instantiate("knight.scene", Vec::new(), &scene, None).unwrap();
+	let copy = NodeTree::from_scene(&scene.read().unwrap(), instance).unwrap();
+
+	// still linked, so it's saved as an instance
+	assert_eq!(copy.root.prefab.unwrap().path.to_str(), Some("knight.scene"));
+	assert_eq!(copy.root.components.len(), 1);
+}
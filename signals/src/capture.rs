@@ -0,0 +1,41 @@
+use std::{rc::{self, Rc}, sync::{self, Arc}};
+
+/// Something a handler can capture without keeping it alive, see `StaticSignal::connect_weak`.
+pub trait WeakCapture: 'static {
+	type Strong;
+
+	/// `None` once (any part of) the captured value is gone.
+	fn upgrade(&self) -> Option<Self::Strong>;
+}
+
+impl<T: ?Sized + 'static> WeakCapture for rc::Weak<T> {
+	type Strong = Rc<T>;
+
+	fn upgrade(&self) -> Option<Self::Strong> {
+		rc::Weak::upgrade(self)
+	}
+}
+
+impl<T: ?Sized + 'static> WeakCapture for sync::Weak<T> {
+	type Strong = Arc<T>;
+
+	fn upgrade(&self) -> Option<Self::Strong> {
+		sync::Weak::upgrade(self)
+	}
+}
+
+impl<A: WeakCapture, B: WeakCapture> WeakCapture for (A, B) {
+	type Strong = (A::Strong, B::Strong);
+
+	fn upgrade(&self) -> Option<Self::Strong> {
+		Some((self.0.upgrade()?, self.1.upgrade()?))
+	}
+}
+
+impl<A: WeakCapture, B: WeakCapture, C: WeakCapture> WeakCapture for (A, B, C) {
+	type Strong = (A::Strong, B::Strong, C::Strong);
+
+	fn upgrade(&self) -> Option<Self::Strong> {
+		Some((self.0.upgrade()?, self.1.upgrade()?, self.2.upgrade()?))
+	}
+}
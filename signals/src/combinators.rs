@@ -0,0 +1,233 @@
+use std::{any::Any, cell::{Cell, RefCell}, rc::{self, Rc}, time::Duration};
+
+use crate::{ConnectOptions, Connection, ConnectionId, Propagation, StaticSignal, WeakCapture};
+
+struct SharedInner<Args: 'static> {
+	signal: RefCell<StaticSignal<Args>>,
+	// derived signals keep the shared signals they're derived from alive, so chains don't fall apart
+	upstream: RefCell<Vec<Rc<dyn Any>>>
+}
+
+/// A reference counted `StaticSignal`, every clone is the same signal. This is what the combinators
+/// in `SignalSource` produce. Their upstream handlers only hold it weakly, so once every clone is
+/// dropped they disconnect themselves.
+pub struct SharedSignal<Args: 'static> {
+	inner: Rc<SharedInner<Args>>
+}
+
+impl<Args> SharedSignal<Args> where Args: 'static {
+	pub fn new() -> Self {
+		Self {
+			inner: Rc::new(SharedInner {
+				signal: RefCell::new(StaticSignal::new()),
+				upstream: RefCell::new(Vec::new())
+			})
+		}
+	}
+
+	pub fn connect<F: Fn(&Args) + 'static>(&self, handler: F) -> Connection {
+		self.inner.signal.borrow_mut().connect(handler)
+	}
+
+	pub fn connect_with<F: Fn(&Args) -> Propagation + 'static>(&self, options: ConnectOptions, handler: F) -> Connection {
+		self.inner.signal.borrow_mut().connect_with(options, handler)
+	}
+
+	pub fn disconnect(&self, id: impl Into<ConnectionId>) -> bool {
+		self.inner.signal.borrow_mut().disconnect(id)
+	}
+
+	pub fn connection_count(&self) -> usize {
+		self.inner.signal.borrow().connection_count()
+	}
+
+	/// Handlers run without the signal borrowed, so they can connect to it or emit it again.
+	pub fn emit(&self, args: Args) -> Propagation {
+		let snapshot = self.inner.signal.borrow().snapshot();
+		snapshot.emit(args)
+	}
+
+	/// Keeps what `source` is derived from alive as long as this signal is.
+	fn keep_alive<S: SignalSource<T>, T: 'static>(&self, source: &S) {
+		if let Some(upstream) = source.upstream() {
+			self.inner.upstream.borrow_mut().push(upstream);
+		}
+	}
+
+	pub fn downgrade(&self) -> WeakSharedSignal<Args> {
+		WeakSharedSignal {
+			inner: Rc::downgrade(&self.inner)
+		}
+	}
+}
+
+impl<Args> Clone for SharedSignal<Args> where Args: 'static {
+	fn clone(&self) -> Self {
+		Self {
+			inner: self.inner.clone()
+		}
+	}
+}
+
+impl<Args> Default for SharedSignal<Args> where Args: 'static {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+pub struct WeakSharedSignal<Args: 'static> {
+	inner: rc::Weak<SharedInner<Args>>
+}
+
+impl<Args> WeakCapture for WeakSharedSignal<Args> where Args: 'static {
+	type Strong = SharedSignal<Args>;
+
+	fn upgrade(&self) -> Option<Self::Strong> {
+		self.inner.upgrade().map(|inner| SharedSignal { inner })
+	}
+}
+
+/// Combinators producing derived signals.
+///
+/// The time-based ones (`throttle`, `debounce`) take a signal emitting frame deltas, e.g. a node's `update` mapped to
+/// its delta, and only count the time it reports.
+pub trait SignalSource<Args: 'static> {
+	/// Connects a handler that only holds `capture` weakly, see `StaticSignal::connect_weak`.
+	fn connect_source<W: WeakCapture, F: Fn(&W::Strong, &Args) + 'static>(&mut self, capture: W, handler: F) -> Connection;
+
+	/// What a signal derived from this one has to keep alive.
+	fn upstream(&self) -> Option<Rc<dyn Any>> { None }
+
+	/// A new signal to derive from this one.
+	fn derived<U: 'static>(&self) -> SharedSignal<U> {
+		let derived = SharedSignal::new();
+
+		if let Some(upstream) = self.upstream() {
+			derived.inner.upstream.borrow_mut().push(upstream);
+		}
+
+		derived
+	}
+
+	fn map<U: 'static, F: Fn(&Args) -> U + 'static>(&mut self, map: F) -> SharedSignal<U> {
+		let derived = self.derived();
+
+		self.connect_source(derived.downgrade(), move |derived, args| {
+			derived.emit(map(args));
+		});
+
+		derived
+	}
+
+	fn filter<F: Fn(&Args) -> bool + 'static>(&mut self, filter: F) -> SharedSignal<Args> where Args: Clone {
+		let derived = self.derived();
+
+		self.connect_source(derived.downgrade(), move |derived, args| {
+			if filter(args) {
+				derived.emit(args.clone());
+			}
+		});
+
+		derived
+	}
+
+	fn filter_map<U: 'static, F: Fn(&Args) -> Option<U> + 'static>(&mut self, filter_map: F) -> SharedSignal<U> {
+		let derived = self.derived();
+
+		self.connect_source(derived.downgrade(), move |derived, args| {
+			if let Some(mapped) = filter_map(args) {
+				derived.emit(mapped);
+			}
+		});
+
+		derived
+	}
+
+	/// Emits at most once per `interval`, dropping whatever comes in between. Time passes with the deltas `frames`
+	/// emits.
+	fn throttle<C: SignalSource<Duration>>(&mut self, interval: Duration, frames: &mut C) -> SharedSignal<Args> where Args: Clone {
+		let derived = self.derived();
+		// since the last emit, none until the first one
+		let elapsed: Rc<Cell<Option<Duration>>> = Rc::default();
+
+		let since = elapsed.clone();
+		frames.connect_source(derived.downgrade(), move |_, delta| {
+			since.set(since.get().map(|since| since + *delta));
+		});
+
+		self.connect_source(derived.downgrade(), move |derived, args| {
+			if elapsed.get().is_none_or(|elapsed| elapsed >= interval) {
+				elapsed.set(Some(Duration::ZERO));
+				derived.emit(args.clone());
+			}
+		});
+
+		derived.keep_alive(frames);
+		derived
+	}
+
+	/// Emits the latest arguments once nothing was emitted for `period`. Time passes with the deltas `frames` emits.
+	fn debounce<C: SignalSource<Duration>>(&mut self, period: Duration, frames: &mut C) -> SharedSignal<Args> where Args: Clone {
+		let derived = self.derived();
+		// the latest arguments and how long ago they came
+		let pending: Rc<RefCell<Option<(Args, Duration)>>> = Rc::default();
+
+		let waiting = pending.clone();
+		frames.connect_source(derived.downgrade(), move |derived, delta| {
+			let settled = {
+				let mut waiting = waiting.borrow_mut();
+
+				match waiting.as_mut() {
+					Some((_, elapsed)) => {
+						*elapsed += *delta;
+						(*elapsed >= period).then(|| waiting.take().unwrap().0)
+					},
+					None => None
+				}
+			};
+
+			if let Some(args) = settled {
+				derived.emit(args);
+			}
+		});
+
+		self.connect_source(derived.downgrade(), move |_, args| {
+			*pending.borrow_mut() = Some((args.clone(), Duration::ZERO));
+		});
+
+		derived.keep_alive(frames);
+		derived
+	}
+
+	/// Emits whenever either this signal or `other` does.
+	fn merge<S: SignalSource<Args>>(&mut self, other: &mut S) -> SharedSignal<Args> where Args: Clone {
+		let derived = self.derived();
+
+		self.connect_source(derived.downgrade(), |derived, args| {
+			derived.emit(args.clone());
+		});
+
+		other.connect_source(derived.downgrade(), |derived, args| {
+			derived.emit(args.clone());
+		});
+
+		derived.keep_alive(other);
+		derived
+	}
+}
+
+impl<Args> SignalSource<Args> for StaticSignal<Args> where Args: 'static {
+	fn connect_source<W: WeakCapture, F: Fn(&W::Strong, &Args) + 'static>(&mut self, capture: W, handler: F) -> Connection {
+		self.connect_weak(capture, handler)
+	}
+}
+
+impl<Args> SignalSource<Args> for SharedSignal<Args> where Args: 'static {
+	fn connect_source<W: WeakCapture, F: Fn(&W::Strong, &Args) + 'static>(&mut self, capture: W, handler: F) -> Connection {
+		self.inner.signal.borrow_mut().connect_weak(capture, handler)
+	}
+
+	fn upstream(&self) -> Option<Rc<dyn Any>> {
+		Some(self.inner.clone())
+	}
+}
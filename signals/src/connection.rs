@@ -0,0 +1,150 @@
+use std::sync::{Arc, atomic::{AtomicBool, AtomicU64, Ordering}};
+
+static NEXT_CONNECTION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Identifies a single handler connected to a signal. Ids are unique for the whole process,
+/// so they can't accidentally match a handler on a different signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ConnectionId(u64);
+
+impl ConnectionId {
+	pub(crate) fn next() -> Self {
+		Self(NEXT_CONNECTION_ID.fetch_add(1, Ordering::Relaxed))
+	}
+
+	pub fn value(&self) -> u64 { self.0 }
+}
+
+/// Handle returned by `connect`. Dropping it does nothing, call `disconnect` or turn it into a
+/// `ScopedConnection` to have the handler removed automatically.
+#[derive(Debug, Clone)]
+pub struct Connection {
+	id: ConnectionId,
+	alive: Arc<AtomicBool>
+}
+
+impl Connection {
+	pub(crate) fn new() -> Self {
+		Self {
+			id: ConnectionId::next(),
+			alive: Arc::new(AtomicBool::new(true))
+		}
+	}
+
+	pub fn id(&self) -> ConnectionId { self.id }
+
+	pub fn is_connected(&self) -> bool {
+		self.alive.load(Ordering::Acquire)
+	}
+
+	/// Disconnects the handler. The signal drops it the next time it's mutated, and won't call it
+	/// again in the meantime.
+	pub fn disconnect(&self) {
+		self.alive.store(false, Ordering::Release);
+	}
+
+	pub fn scoped(self) -> ScopedConnection {
+		ScopedConnection { connection: Some(self) }
+	}
+}
+
+impl From<Connection> for ConnectionId {
+	fn from(connection: Connection) -> Self {
+		connection.id
+	}
+}
+
+/// Disconnects its handler when dropped.
+#[derive(Debug)]
+#[must_use = "the handler is disconnected as soon as the ScopedConnection is dropped"]
+pub struct ScopedConnection {
+	connection: Option<Connection>
+}
+
+impl ScopedConnection {
+	pub fn id(&self) -> ConnectionId {
+		self.connection.as_ref().unwrap().id()
+	}
+
+	pub fn is_connected(&self) -> bool {
+		self.connection.as_ref().unwrap().is_connected()
+	}
+
+	/// Keeps the handler connected after the guard is dropped.
+	pub fn release(mut self) -> Connection {
+		self.connection.take().unwrap()
+	}
+}
+
+impl Drop for ScopedConnection {
+	fn drop(&mut self) {
+		if let Some(connection) = self.connection.take() {
+			connection.disconnect();
+		}
+	}
+}
+
+/// How a handler is connected, see `StaticSignal::connect_with`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ConnectOptions {
+	/// Handlers with a higher priority run first. Handlers with the same priority run in the order they were connected.
+	pub priority: i32,
+	/// Disconnect the handler after it ran once.
+	pub once: bool
+}
+
+impl ConnectOptions {
+	pub fn with_priority(priority: i32) -> Self {
+		Self { priority, once: false }
+	}
+
+	pub fn once() -> Self {
+		Self { priority: 0, once: true }
+	}
+}
+
+/// A connected handler together with its liveness flag.
+#[derive(Clone)]
+pub(crate) struct Slot<H> {
+	pub(crate) id: ConnectionId,
+	pub(crate) alive: Arc<AtomicBool>,
+	pub(crate) priority: i32,
+	pub(crate) once: bool,
+	pub(crate) handler: H
+}
+
+impl<H> Slot<H> {
+	pub(crate) fn new(handler: H, options: ConnectOptions) -> (Self, Connection) {
+		let connection = Connection::new();
+		(Self::with_connection(&connection, handler, options), connection)
+	}
+
+	/// For handlers that need their own `Connection` before they're connected.
+	pub(crate) fn with_connection(connection: &Connection, handler: H, options: ConnectOptions) -> Self {
+		Self {
+			id: connection.id,
+			alive: connection.alive.clone(),
+			priority: options.priority,
+			once: options.once,
+			handler
+		}
+	}
+
+	pub(crate) fn is_alive(&self) -> bool {
+		self.alive.load(Ordering::Acquire)
+	}
+
+	/// Whether the handler should be called now. One-shot handlers are disconnected on the spot,
+	/// so a nested emit can't run them a second time.
+	pub(crate) fn begin_call(&self) -> bool {
+		if self.once {
+			self.alive.swap(false, Ordering::AcqRel)
+		} else {
+			self.is_alive()
+		}
+	}
+
+	pub(crate) fn kill(&self) {
+		self.alive.store(false, Ordering::Release);
+	}
+}
@@ -1,6 +1,6 @@
 use std::{any::Any, collections::HashMap};
 
-use crate::{Signal, StaticSignal};
+use crate::{ConnectOptions, Connection, ConnectionId, Propagation, Signal, SignalFuture, SignalKey, SignalQueue, SignalSnapshot, StaticSignal};
 
 pub struct SignalDispatcher {
 	signals: HashMap<String, Box<dyn Signal>>
@@ -14,32 +14,109 @@ impl SignalDispatcher {
 	}
 
 	pub fn create_signal<Args: Copy + 'static>(&mut self, name: &str) {
-		let signal = StaticSignal::<Args>::new();
+		let signal = StaticSignal::<Args>::with_name(name);
 		self.signals.insert(name.to_string(), Box::new(signal));
 	}
 
-	pub fn connect<Args: Copy + 'static, F: Fn(&Args) -> () + 'static>(&mut self, name: &str, handler: F) {
+	pub fn connect<Args: Copy + 'static, F: Fn(&Args) -> () + 'static>(&mut self, name: &str, handler: F) -> Connection {
 		let signal = self.signals.get_mut(&name.to_string())
 			.expect(format!("No such signal: {}", name).as_str());
 
 		// A Box Box !
 		let handler = Box::new(Box::new(handler) as Box<dyn Fn(&Args)>) as Box<dyn Any>;
-		signal.connect_any(handler);
+		signal.connect_any(handler)
 	}
 
-	pub fn disconnect<Args: Copy + 'static, F: Fn(&Args) -> () + 'static>(&mut self, name: &str, handler: F) {
+	pub fn connect_with<Args: Copy + 'static, F: Fn(&Args) -> Propagation + 'static>(&mut self, name: &str, options: ConnectOptions, handler: F) -> Connection {
+		let signal = self.signals.get_mut(name)
+			.unwrap_or_else(|| panic!("No such signal: {}", name));
+
+		let handler = Box::new(Box::new(handler) as Box<dyn Fn(&Args) -> Propagation>) as Box<dyn Any>;
+		signal.connect_any_with(options, handler)
+	}
+
+	pub fn disconnect(&mut self, name: &str, id: impl Into<ConnectionId>) -> bool {
 		let signal = self.signals.get_mut(&name.to_string())
 			.expect(format!("No such signal: {}", name).as_str());
 
-		// Box Box <3
-		let handler = Box::new(Box::new(handler) as Box<dyn Fn(&Args)>) as Box<dyn Any>;
-		signal.disconnect_any(handler);
+		signal.disconnect_any(id.into())
 	}
 
-	pub fn emit<Args: Copy + 'static>(&self, name: &str, args: Args) {
+	pub fn emit<Args: Copy + 'static>(&self, name: &str, args: Args) -> Propagation {
 		let signal = self.signals.get(&name.to_string())
 			.expect(format!("No such signal: {}", name).as_str());
 
-		signal.emit_any(&args);
+		signal.emit_any(&args)
+	}
+
+	/// See `StaticSignal::emit_deferred`, only defers on the thread that flushes the `SignalQueue`.
+	pub fn emit_deferred<Args: Copy + 'static>(&self, name: &str, args: Args) {
+		let signal = self.signals.get(name)
+			.unwrap_or_else(|| panic!("No such signal: {}", name));
+
+		let snapshot = signal.snapshot_any()
+			.downcast::<SignalSnapshot<Args>>()
+			.expect("Signal called with invalid arguments");
+
+		SignalQueue::push(move || { snapshot.emit(args); });
+	}
+
+	// typed keys
+
+	// The keyed methods panic if the signal exists with other arguments than the key's, e.g. because it was created
+	// through another key with the same name.
+
+	/// Connects to the signal named by `key`, creating it if it doesn't exist yet.
+	pub fn connect_key<Args: Copy + 'static, F: Fn(&Args) + 'static>(&mut self, key: SignalKey<Args>, handler: F) -> Connection {
+		self.key_signal(key);
+		self.connect(key.name(), handler)
+	}
+
+	/// Emits the signal named by `key`. A signal nobody has connected to yet has no handlers to call,
+	/// so that's not an error.
+	pub fn emit_key<Args: Copy + 'static>(&self, key: SignalKey<Args>, args: Args) -> Propagation {
+		match self.existing_key_signal(key) {
+			Some(signal) => signal.emit_any(&args),
+			None => Propagation::Continue
+		}
+	}
+
+	pub fn emit_key_deferred<Args: Copy + 'static>(&self, key: SignalKey<Args>, args: Args) {
+		if self.existing_key_signal(key).is_some() {
+			self.emit_deferred(key.name(), args);
+		}
+	}
+
+	/// Resolves with the arguments of the next emission of the signal named by `key`.
+	pub fn next_key<Args: Copy + 'static>(&mut self, key: SignalKey<Args>) -> SignalFuture<Args> {
+		self.key_signal(key);
+		SignalFuture::new(|handler| self.connect_with(key.name(), ConnectOptions::once(), handler))
+	}
+
+	fn key_signal<Args: Copy + 'static>(&mut self, key: SignalKey<Args>) -> &mut Box<dyn Signal> {
+		let signal = self.signals.entry(key.name().to_string())
+			.or_insert_with(|| Box::new(StaticSignal::<Args>::with_name(key.name())));
+
+		signal.expect_args::<Args>(key.name());
+		signal
+	}
+
+	fn existing_key_signal<Args: Copy + 'static>(&self, key: SignalKey<Args>) -> Option<&dyn Signal> {
+		let signal = self.signals.get(key.name())?.as_ref();
+		signal.expect_args::<Args>(key.name());
+		Some(signal)
+	}
+
+	#[cfg(feature = "instrumentation")]
+	pub fn stats(&self, name: &str) -> Option<crate::SignalStats> {
+		self.signals.get(name).map(|signal| signal.stats())
+	}
+
+	/// Stats of every signal, by name.
+	#[cfg(feature = "instrumentation")]
+	pub fn all_stats(&self) -> HashMap<String, crate::SignalStats> {
+		self.signals.iter()
+			.map(|(name, signal)| (name.clone(), signal.stats()))
+			.collect()
 	}
 }
@@ -0,0 +1,237 @@
+use std::{cell::{Cell, RefCell}, collections::{HashMap, HashSet}, future::Future, pin::Pin, rc::Rc, sync::{Arc, Mutex}, task::{Context, Poll, Wake, Waker}, time::Duration};
+
+type TaskId = u64;
+
+struct Task {
+	future: Pin<Box<dyn Future<Output = ()>>>,
+	state: Rc<TaskState>
+}
+
+#[derive(Default)]
+struct TaskState {
+	finished: Cell<bool>,
+	cancelled: Cell<bool>
+}
+
+struct TaskWaker {
+	id: TaskId,
+	ready: Arc<Mutex<Vec<TaskId>>>
+}
+
+impl Wake for TaskWaker {
+	fn wake(self: Arc<Self>) {
+		self.ready.lock().unwrap().push(self.id);
+	}
+}
+
+#[derive(Default)]
+struct ExecutorState {
+	next_id: TaskId,
+	tasks: HashMap<TaskId, Task>,
+	ready: Arc<Mutex<Vec<TaskId>>>,
+
+	time: Duration,
+	frame: u64,
+	timers: Vec<(Duration, Waker)>,
+	frame_waiters: Vec<Waker>
+}
+
+thread_local! {
+	static EXECUTOR: RefCell<ExecutorState> = RefCell::new(ExecutorState::default());
+}
+
+/// Per-thread executor for gameplay coroutines. `CoreEngine` updates it once per frame with the frame
+/// delta, which is also what `sleep` counts, so coroutines are paused along with the game loop.
+pub struct Executor;
+
+impl Executor {
+	/// Spawns a coroutine. It's first polled on the next `update`.
+	pub fn spawn<F: Future<Output = ()> + 'static>(future: F) -> TaskHandle {
+		EXECUTOR.with_borrow_mut(|executor| {
+			let id = executor.next_id;
+			executor.next_id += 1;
+
+			let state = Rc::new(TaskState::default());
+
+			executor.tasks.insert(id, Task {
+				future: Box::pin(future),
+				state: state.clone()
+			});
+
+			executor.ready.lock().unwrap().push(id);
+
+			TaskHandle { id, state }
+		})
+	}
+
+	/// Advances the executor's clock by `delta`, then polls every coroutine that was woken up.
+	/// A coroutine is polled at most once per update, wake-ups after that are handled on the next one.
+	pub fn update(delta: Duration) {
+		let wakers = EXECUTOR.with_borrow_mut(|executor| {
+			executor.time += delta;
+			executor.frame += 1;
+
+			let time = executor.time;
+			let (elapsed, timers) = std::mem::take(&mut executor.timers)
+				.into_iter()
+				.partition::<Vec<_>, _>(|(deadline, _)| *deadline <= time);
+
+			executor.timers = timers;
+
+			let mut wakers: Vec<Waker> = elapsed.into_iter().map(|(_, waker)| waker).collect();
+			wakers.append(&mut executor.frame_waiters);
+			wakers
+		});
+
+		for waker in wakers {
+			waker.wake();
+		}
+
+		let ready = EXECUTOR.with_borrow(|executor| executor.ready.clone());
+		let mut polled = HashSet::new();
+		let mut next_update = Vec::new();
+
+		loop {
+			let woken = std::mem::take(&mut *ready.lock().unwrap());
+
+			if woken.is_empty() {
+				break;
+			}
+
+			for id in woken {
+				if polled.insert(id) {
+					Self::poll_task(id, &ready);
+				} else if !next_update.contains(&id) {
+					next_update.push(id);
+				}
+			}
+		}
+
+		// woken again after they were polled
+		ready.lock().unwrap().extend(next_update);
+	}
+
+	fn poll_task(id: TaskId, ready: &Arc<Mutex<Vec<TaskId>>>) {
+		// taken out of the executor while it runs, so it can spawn and cancel tasks
+		let Some(mut task) = EXECUTOR.with_borrow_mut(|executor| executor.tasks.remove(&id)) else {
+			return; // finished or cancelled
+		};
+
+		if task.state.cancelled.get() {
+			return;
+		}
+
+		let waker = Waker::from(Arc::new(TaskWaker { id, ready: ready.clone() }));
+		let mut context = Context::from_waker(&waker);
+
+		match task.future.as_mut().poll(&mut context) {
+			Poll::Ready(()) => task.state.finished.set(true),
+			Poll::Pending if task.state.cancelled.get() => (),
+			Poll::Pending => {
+				EXECUTOR.with_borrow_mut(|executor| executor.tasks.insert(id, task));
+			}
+		}
+	}
+
+	/// Time the executor has been updated with so far.
+	pub fn time() -> Duration {
+		EXECUTOR.with_borrow(|executor| executor.time)
+	}
+
+	pub fn task_count() -> usize {
+		EXECUTOR.with_borrow(|executor| executor.tasks.len())
+	}
+
+	/// Resolves once `duration` of frame time has passed.
+	pub fn sleep(duration: Duration) -> Sleep {
+		Sleep {
+			duration,
+			deadline: None
+		}
+	}
+
+	/// Resolves on the next `update`.
+	pub fn next_frame() -> NextFrame {
+		NextFrame { frame: None }
+	}
+}
+
+/// Handle to a spawned coroutine.
+#[derive(Clone)]
+pub struct TaskHandle {
+	id: TaskId,
+	state: Rc<TaskState>
+}
+
+impl TaskHandle {
+	pub fn is_finished(&self) -> bool { self.state.finished.get() }
+	pub fn is_cancelled(&self) -> bool { self.state.cancelled.get() }
+
+	/// Stops the coroutine, it won't be polled again.
+	pub fn cancel(&self) {
+		if self.is_finished() || self.is_cancelled() {
+			return;
+		}
+
+		self.state.cancelled.set(true);
+
+		// dropped outside of the borrow, the future may own things that talk to the executor
+		let task = EXECUTOR.with_borrow_mut(|executor| executor.tasks.remove(&self.id));
+		drop(task);
+	}
+}
+
+impl std::fmt::Debug for TaskHandle {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("TaskHandle")
+			.field("id", &self.id)
+			.field("finished", &self.is_finished())
+			.field("cancelled", &self.is_cancelled())
+			.finish()
+	}
+}
+
+pub struct Sleep {
+	duration: Duration,
+	deadline: Option<Duration>
+}
+
+impl Future for Sleep {
+	type Output = ();
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		EXECUTOR.with_borrow_mut(|executor| {
+			// counted from the first poll, not from when the future was created
+			let duration = self.duration;
+			let deadline = *self.deadline.get_or_insert(executor.time + duration);
+
+			if executor.time >= deadline {
+				return Poll::Ready(());
+			}
+
+			executor.timers.push((deadline, cx.waker().clone()));
+			Poll::Pending
+		})
+	}
+}
+
+pub struct NextFrame {
+	frame: Option<u64>
+}
+
+impl Future for NextFrame {
+	type Output = ();
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		EXECUTOR.with_borrow_mut(|executor| {
+			let frame = *self.frame.get_or_insert(executor.frame);
+
+			if executor.frame > frame {
+				return Poll::Ready(());
+			}
+
+			executor.frame_waiters.push(cx.waker().clone());
+			Poll::Pending
+		})
+	}
+}
@@ -0,0 +1,67 @@
+use std::{cell::RefCell, future::Future, pin::Pin, rc::Rc, task::{Context, Poll, Waker}};
+
+use crate::{Connection, Propagation};
+
+struct FutureState<Args> {
+	value: Option<Args>,
+	waker: Option<Waker>
+}
+
+/// Resolves with the arguments of the next emission of a signal, see `StaticSignal::next`.
+/// Dropping it before that disconnects its handler.
+pub struct SignalFuture<Args: 'static> {
+	state: Rc<RefCell<FutureState<Args>>>,
+	connection: Connection
+}
+
+impl<Args> SignalFuture<Args> where Args: Clone + 'static {
+	/// Builds the future around a handler that `connect` connects to some signal, for signals that
+	/// aren't a `StaticSignal`. The handler should be connected with `ConnectOptions::once()`.
+	pub fn new(connect: impl FnOnce(Box<dyn Fn(&Args) -> Propagation>) -> Connection) -> Self {
+		let state = Rc::new(RefCell::new(FutureState {
+			value: None,
+			waker: None
+		}));
+
+		let handler_state = Rc::downgrade(&state);
+
+		let connection = connect(Box::new(move |args| {
+			if let Some(state) = handler_state.upgrade() {
+				let mut state = state.borrow_mut();
+				state.value = Some(args.clone());
+
+				if let Some(waker) = state.waker.take() {
+					waker.wake();
+				}
+			}
+
+			Propagation::Continue
+		}));
+
+		Self {
+			state,
+			connection
+		}
+	}
+}
+
+impl<Args> Future for SignalFuture<Args> where Args: 'static {
+	type Output = Args;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let mut state = self.state.borrow_mut();
+
+		if let Some(value) = state.value.take() {
+			return Poll::Ready(value);
+		}
+
+		state.waker = Some(cx.waker().clone());
+		Poll::Pending
+	}
+}
+
+impl<Args> Drop for SignalFuture<Args> where Args: 'static {
+	fn drop(&mut self) {
+		self.connection.disconnect();
+	}
+}
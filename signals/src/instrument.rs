@@ -0,0 +1,125 @@
+//! Signal instrumentation, only does something with the `instrumentation` feature enabled.
+//! Emissions and handler calls are recorded into `SignalStats` and wrapped in `tracing` spans
+//! (`signal_emit` and `signal_handler`, both at the TRACE level).
+
+use std::{collections::HashMap, time::Duration};
+
+use crate::ConnectionId;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HandlerStats {
+	pub calls: u64,
+	pub total_time: Duration,
+	pub max_time: Duration
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SignalStats {
+	pub emits: u64,
+	pub handler_calls: u64,
+	pub total_time: Duration,
+	pub handlers: HashMap<ConnectionId, HandlerStats>
+}
+
+#[cfg(feature = "instrumentation")]
+mod enabled {
+	use std::{cell::RefCell, rc::Rc, time::Instant};
+
+	use crate::{ConnectionId, SignalStats};
+
+	#[derive(Clone, Default)]
+	pub(crate) struct Instrument {
+		name: Rc<str>,
+		stats: Rc<RefCell<SignalStats>>
+	}
+
+	impl Instrument {
+		pub(crate) fn new(name: Rc<str>) -> Self {
+			Self {
+				name,
+				stats: Default::default()
+			}
+		}
+
+		pub(crate) fn stats(&self) -> SignalStats {
+			self.stats.borrow().clone()
+		}
+
+		pub(crate) fn reset(&self) {
+			*self.stats.borrow_mut() = SignalStats::default();
+		}
+
+		pub(crate) fn emit(&self, handlers: usize) -> EmitGuard<'_> {
+			EmitGuard {
+				instrument: self,
+				start: Instant::now(),
+				_span: tracing::trace_span!("signal_emit", signal = &*self.name, handlers).entered()
+			}
+		}
+
+		pub(crate) fn handler(&self, id: ConnectionId) -> HandlerGuard<'_> {
+			HandlerGuard {
+				instrument: self,
+				id,
+				start: Instant::now(),
+				_span: tracing::trace_span!("signal_handler", signal = &*self.name, connection = id.value()).entered()
+			}
+		}
+	}
+
+	pub(crate) struct EmitGuard<'a> {
+		instrument: &'a Instrument,
+		start: Instant,
+		_span: tracing::span::EnteredSpan
+	}
+
+	impl Drop for EmitGuard<'_> {
+		fn drop(&mut self) {
+			let mut stats = self.instrument.stats.borrow_mut();
+			stats.emits += 1;
+			stats.total_time += self.start.elapsed();
+		}
+	}
+
+	pub(crate) struct HandlerGuard<'a> {
+		instrument: &'a Instrument,
+		id: ConnectionId,
+		start: Instant,
+		_span: tracing::span::EnteredSpan
+	}
+
+	impl Drop for HandlerGuard<'_> {
+		fn drop(&mut self) {
+			let elapsed = self.start.elapsed();
+
+			let mut stats = self.instrument.stats.borrow_mut();
+			stats.handler_calls += 1;
+
+			let handler = stats.handlers.entry(self.id).or_default();
+			handler.calls += 1;
+			handler.total_time += elapsed;
+			handler.max_time = handler.max_time.max(elapsed);
+		}
+	}
+}
+
+#[cfg(feature = "instrumentation")]
+pub(crate) use enabled::Instrument;
+
+#[cfg(not(feature = "instrumentation"))]
+#[derive(Clone, Default)]
+pub(crate) struct Instrument;
+
+#[cfg(not(feature = "instrumentation"))]
+pub(crate) struct NoopGuard;
+
+#[cfg(not(feature = "instrumentation"))]
+impl Instrument {
+	pub(crate) fn new(_name: std::rc::Rc<str>) -> Self { Self }
+
+	#[inline(always)]
+	pub(crate) fn emit(&self, _handlers: usize) -> NoopGuard { NoopGuard }
+
+	#[inline(always)]
+	pub(crate) fn handler(&self, _id: ConnectionId) -> NoopGuard { NoopGuard }
+}
@@ -0,0 +1,60 @@
+use std::{fmt::Debug, hash::Hash, marker::PhantomData};
+
+/// Names a signal together with its argument type, so connecting and emitting through it is checked
+/// at compile time:
+///
+/// ```
+/// use fatum_signals::{SignalDispatcher, SignalKey};
+///
+/// const SCORE_CHANGED: SignalKey<u32> = SignalKey::new("score_changed");
+///
+/// let mut dispatcher = SignalDispatcher::new();
+/// dispatcher.connect_key(SCORE_CHANGED, |score| println!("score: {}", score));
+/// dispatcher.emit_key(SCORE_CHANGED, 100);
+/// ```
+pub struct SignalKey<Args: 'static> {
+	name: &'static str,
+	_marker: PhantomData<fn(Args)>
+}
+
+impl<Args> SignalKey<Args> where Args: 'static {
+	pub const fn new(name: &'static str) -> Self {
+		Self {
+			name,
+			_marker: PhantomData
+		}
+	}
+
+	pub const fn name(&self) -> &'static str { self.name }
+}
+
+impl<Args> Clone for SignalKey<Args> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+
+impl<Args> Copy for SignalKey<Args> {}
+
+impl<Args> PartialEq for SignalKey<Args> {
+	fn eq(&self, other: &Self) -> bool {
+		self.name == other.name
+	}
+}
+
+impl<Args> Eq for SignalKey<Args> {}
+
+impl<Args> Hash for SignalKey<Args> {
+	fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+		self.name.hash(state);
+	}
+}
+
+impl<Args> Debug for SignalKey<Args> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_tuple("SignalKey")
+			.field(&self.name)
+			.field(&std::any::type_name::<Args>())
+			.finish()
+	}
+}
@@ -1,7 +1,37 @@
 #![feature(fn_traits)]
 
+mod connection;
+pub use connection::*;
+
+mod capture;
+pub use capture::*;
+
+mod key;
+pub use key::*;
+
+mod instrument;
+pub use instrument::{HandlerStats, SignalStats};
+
 mod signal;
 pub use signal::*;
 
+mod queue;
+pub use queue::*;
+
+mod future;
+pub use future::*;
+
+mod executor;
+pub use executor::*;
+
+mod combinators;
+pub use combinators::*;
+
 mod dispatcher;
 pub use dispatcher::*;
+
+mod sync_signal;
+pub use sync_signal::*;
+
+mod sync_dispatcher;
+pub use sync_dispatcher::*;
@@ -0,0 +1,63 @@
+use std::{cell::RefCell, collections::VecDeque, sync::OnceLock, thread::{self, ThreadId}};
+
+thread_local! {
+	static QUEUE: RefCell<VecDeque<Box<dyn FnOnce()>>> = RefCell::new(VecDeque::new());
+}
+
+static FLUSHING_THREAD: OnceLock<ThreadId> = OnceLock::new();
+
+/// Per-thread queue of deferred signal emissions. `CoreEngine` flushes it once per frame on the main thread,
+/// after the scene has been processed and no scene locks are held anymore.
+///
+/// Only the queue of the thread that flushes it is ever emptied, so deferring only works on that thread. Once
+/// it's known through `set_flushing_thread`, emissions deferred on other threads, e.g. the rayon thread pool,
+/// are logged and emitted right away instead of being lost.
+pub struct SignalQueue;
+
+impl SignalQueue {
+	pub fn push<F: FnOnce() + 'static>(emission: F) {
+		if let Some(flushing) = FLUSHING_THREAD.get() && *flushing != thread::current().id() {
+			log::warn!("Deferred a signal emission on {:?}, whose queue is never flushed. Emitting it right away", thread::current());
+			emission();
+			return;
+		}
+
+		QUEUE.with_borrow_mut(|queue| queue.push_back(Box::new(emission)));
+	}
+
+	/// Makes the calling thread the one whose queue is flushed, see `SignalQueue`. Can only be set once, returns
+	/// `false` if it was already set to another thread.
+	pub fn set_flushing_thread() -> bool {
+		let current = thread::current().id();
+		*FLUSHING_THREAD.get_or_init(|| current) == current
+	}
+
+	pub fn len() -> usize {
+		QUEUE.with_borrow(|queue| queue.len())
+	}
+
+	pub fn is_empty() -> bool {
+		Self::len() == 0
+	}
+
+	/// Runs every emission queued so far and returns how many ran. Emissions queued by handlers
+	/// during the flush are kept for the next one, so a handler re-emitting itself can't stall the frame.
+	pub fn flush() -> usize {
+		let count = Self::len();
+
+		for _ in 0..count {
+			// don't hold the borrow while the handlers run, they're allowed to queue more
+			let emission = QUEUE.with_borrow_mut(|queue| queue.pop_front());
+
+			if let Some(emission) = emission {
+				emission();
+			}
+		}
+
+		count
+	}
+
+	pub fn clear() {
+		QUEUE.with_borrow_mut(|queue| queue.clear());
+	}
+}
@@ -1,78 +1,222 @@
-use std::{any::Any, collections::HashMap, sync::{Arc, RwLock, RwLockReadGuard}};
+use std::{any::{Any, TypeId, type_name}, rc::Rc};
+
+use crate::{ConnectOptions, Connection, ConnectionId, SignalFuture, SignalQueue, Slot, WeakCapture, instrument::Instrument};
+
+/// Returned by handlers connected with `StaticSignal::connect_with` / `connect_priority`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Propagation {
+	#[default]
+	Continue,
+	/// Don't call any of the remaining (lower priority) handlers.
+	Stop
+}
 
 pub trait Signal {
 	/// This function is DANGEROUS and should be used with CAUTION!!!
-	fn connect_any(&mut self, handler: Box<dyn Any>);
+	fn connect_any(&mut self, handler: Box<dyn Any>) -> Connection;
 
 	/// This function is DANGEROUS and should be used with CAUTION!!!
-	fn disconnect_any(&mut self, handler: Box<dyn Any>);
+	/// Unlike `connect_any`, the handler has to return a `Propagation`.
+	fn connect_any_with(&mut self, options: ConnectOptions, handler: Box<dyn Any>) -> Connection;
+
+	fn disconnect_any(&mut self, id: ConnectionId) -> bool;
 
 	/// This function is DANGEROUS and should be used with CAUTION!!!
-	fn emit_any(&self, args: &dyn Any);
+	fn emit_any(&self, args: &dyn Any) -> Propagation;
+
+	/// Returns a `Box<SignalSnapshot<Args>>` of the currently connected handlers.
+	fn snapshot_any(&self) -> Box<dyn Any>;
+
+	#[cfg(feature = "instrumentation")]
+	fn stats(&self) -> crate::SignalStats;
 
 	fn clear(&mut self);
+
+	/// The `TypeId` of the arguments the signal is emitted with.
+	fn args_type_id(&self) -> TypeId;
+
+	fn args_type_name(&self) -> &'static str;
+}
+
+impl dyn Signal {
+	/// Whether the signal is emitted with `Args`.
+	pub fn takes<Args: 'static>(&self) -> bool {
+		self.args_type_id() == TypeId::of::<Args>()
+	}
+
+	/// Panics unless the signal is emitted with `Args`, e.g. because two keys with different arguments share
+	/// the signal's `name`.
+	pub fn expect_args<Args: 'static>(&self, name: &str) {
+		if !self.takes::<Args>() {
+			panic!("Signal {} takes {}, not {}", name, self.args_type_name(), type_name::<Args>());
+		}
+	}
 }
 
+type Handler<Args> = Rc<dyn Fn(&Args) -> Propagation>;
+
 pub struct StaticSignal<Args: 'static> {
-	handlers: Vec<Box<dyn Fn(&Args) -> ()>>,
-	capture_handlers: Vec<Box<dyn Fn(&Vec<*mut std::ffi::c_void>, &Args) -> ()>>,
-	captures: Vec<Vec<*mut std::ffi::c_void>>
+	name: Rc<str>,
+	// sorted by priority, highest first
+	slots: Vec<Slot<Handler<Args>>>,
+	instrument: Instrument
 }
 
 impl<Args> StaticSignal<Args> where Args: 'static {
 	pub fn new() -> Self {
+		Self::with_name(std::any::type_name::<Self>())
+	}
+
+	/// The name only shows up in instrumentation.
+	pub fn with_name(name: &str) -> Self {
+		let name: Rc<str> = Rc::from(name);
+
 		Self {
-			handlers: Vec::new(),
-			capture_handlers: Vec::new(),
-			captures: Vec::new()
+			name: name.clone(),
+			slots: Vec::new(),
+			instrument: Instrument::new(name)
 		}
 	}
 
-	pub fn connect<F: Fn(&Args) -> () + 'static>(&mut self, handler: F) {
-		self.handlers.push(Box::new(handler));
+	pub fn name(&self) -> &str { &self.name }
+
+	pub fn connect<F: Fn(&Args) -> () + 'static>(&mut self, handler: F) -> Connection {
+		self.connect_with(ConnectOptions::default(), move |args| {
+			handler(args);
+			Propagation::Continue
+		})
 	}
 
-	// i don't think i can even explain this one
-	pub fn connect_capture<F: Fn(&Vec<*mut std::ffi::c_void>, &Args) -> () + 'static>(&mut self, capture: Vec<*mut std::ffi::c_void>, handler: F) {
-		self.capture_handlers.push(Box::new(handler));
-		self.captures.push(capture);
+	/// Connects a handler that is disconnected after the first time it runs.
+	pub fn connect_once<F: Fn(&Args) -> () + 'static>(&mut self, handler: F) -> Connection {
+		self.connect_with(ConnectOptions::once(), move |args| {
+			handler(args);
+			Propagation::Continue
+		})
+	}
+
+	/// Connects a handler that runs before every handler with a lower priority, and can stop them from running.
+	/// Plain `connect` uses priority 0.
+	pub fn connect_priority<F: Fn(&Args) -> Propagation + 'static>(&mut self, priority: i32, handler: F) -> Connection {
+		self.connect_with(ConnectOptions::with_priority(priority), handler)
 	}
 
-	pub fn disconnect<F: Fn(&Args) -> () + 'static>(&mut self, handler: F) {
-		let mut index: Option<usize> = None;
+	pub fn connect_with<F: Fn(&Args) -> Propagation + 'static>(&mut self, options: ConnectOptions, handler: F) -> Connection {
+		self.insert(options, Rc::new(handler))
+	}
+
+	/// Connects a handler that only holds `capture` weakly. It's upgraded every time the signal is emitted,
+	/// and the handler disconnects itself once the captured value is gone.
+	pub fn connect_weak<W: WeakCapture, F: Fn(&W::Strong, &Args) + 'static>(&mut self, capture: W, handler: F) -> Connection {
+		self.prune();
+
+		let connection = Connection::new();
+		let this = connection.clone();
 
-		for i in 0..self.handlers.len() {
-			let e_handler = &self.handlers[i];
-			// derefdereferencing a &Box<T> into T waowww
-			if (**e_handler).type_id() == handler.type_id() {
-				index = Some(i);
-				break;
+		let handler: Handler<Args> = Rc::new(move |args| {
+			match capture.upgrade() {
+				Some(capture) => handler(&capture, args),
+				None => this.disconnect()
 			}
-		}
 
-		if index.is_none() {
-			log::warn!("Not removing handler {:?}: doesn't exist in the current signal", handler.type_id());
-		}
+			Propagation::Continue
+		});
+
+		self.insert_slot(Slot::with_connection(&connection, handler, ConnectOptions::default()));
+		connection
+	}
+
+	// i don't think i can even explain this one
+	#[deprecated = "raw pointer captures dangle as soon as their target moves, use connect_weak instead"]
+	pub fn connect_capture<F: Fn(&Vec<*mut std::ffi::c_void>, &Args) -> () + 'static>(&mut self, capture: Vec<*mut std::ffi::c_void>, handler: F) -> Connection {
+		self.connect(move |args| handler(&capture, args))
+	}
+
+	fn insert(&mut self, options: ConnectOptions, handler: Handler<Args>) -> Connection {
+		self.prune();
 
-		self.handlers.remove(index.unwrap());
+		let (slot, connection) = Slot::new(handler, options);
+		self.insert_slot(slot);
+		connection
 	}
 
-	pub fn emit(&self, args: Args) {
-		for handler in &self.handlers {
-			(handler.as_ref())(&args);
+	fn insert_slot(&mut self, slot: Slot<Handler<Args>>) {
+		// after everything with the same priority, so equal priorities keep the connection order
+		let index = self.slots.partition_point(|other| other.priority >= slot.priority);
+		self.slots.insert(index, slot);
+	}
+
+	/// Resolves with the arguments of the next emission.
+	#[allow(clippy::should_implement_trait)]
+	pub fn next(&mut self) -> SignalFuture<Args> where Args: Clone {
+		SignalFuture::new(|handler| self.connect_with(ConnectOptions::once(), handler))
+	}
+
+	/// Disconnects exactly the handler identified by `id`. Returns `false` if it isn't connected
+	/// to this signal.
+	pub fn disconnect(&mut self, id: impl Into<ConnectionId>) -> bool {
+		let id = id.into();
+
+		if let Some(index) = self.slots.iter().position(|slot| slot.id == id) {
+			let slot = self.slots.remove(index);
+			slot.kill();
+			return true;
 		}
 
-		for i in 0..self.capture_handlers.len() {
-			let handler = &self.capture_handlers[i];
-			let capture = &self.captures[i];
+		log::warn!("Not removing handler {:?}: doesn't exist in the current signal", id);
+		false
+	}
+
+	pub fn is_connected(&self, id: ConnectionId) -> bool {
+		self.slots.iter().any(|slot| slot.id == id && slot.is_alive())
+	}
+
+	pub fn connection_count(&self) -> usize {
+		self.slots.iter().filter(|slot| slot.is_alive()).count()
+	}
+
+	/// Calls the handlers in priority order. Returns `Propagation::Stop` if one of them stopped the emission.
+	pub fn emit(&self, args: Args) -> Propagation {
+		emit_slots(&self.slots, &args, &self.instrument)
+	}
+
+	/// Queues the emission on the `SignalQueue` instead of running the handlers right away.
+	/// The handlers connected *now* are the ones called, unless they get disconnected before the flush.
+	/// Only deferred on the thread that flushes the queue, usually the main one.
+	pub fn emit_deferred(&self, args: Args) {
+		let snapshot = self.snapshot();
+		SignalQueue::push(move || { snapshot.emit(args); });
+	}
 
-			(handler.as_ref())(capture, &args);
+	pub fn snapshot(&self) -> SignalSnapshot<Args> {
+		SignalSnapshot {
+			slots: self.slots.iter()
+				.filter(|slot| slot.is_alive())
+				.cloned()
+				.collect(),
+			instrument: self.instrument.clone()
 		}
 	}
+
+	/// What was recorded about this signal's emissions so far.
+	#[cfg(feature = "instrumentation")]
+	pub fn stats(&self) -> crate::SignalStats {
+		self.instrument.stats()
+	}
+
+	#[cfg(feature = "instrumentation")]
+	pub fn reset_stats(&self) {
+		self.instrument.reset();
+	}
+
+	/// Drops handlers whose `Connection` was disconnected.
+	fn prune(&mut self) {
+		self.slots.retain(|slot| slot.is_alive());
+	}
 }
 
 impl<Args> Signal for StaticSignal<Args> where Args: 'static {
-	fn connect_any(&mut self, handler: Box<dyn Any>) {
+	fn connect_any(&mut self, handler: Box<dyn Any>) -> Connection {
 		let handler_type_name = std::any::type_name_of_val(&handler);
 
 		// Box !
@@ -83,43 +227,89 @@ impl<Args> Signal for StaticSignal<Args> where Args: 'static {
 				handler_type_name
 			).as_str());
 
-		self.handlers.push(handler);
+		self.connect(handler)
 	}
 
-	fn disconnect_any(&mut self, handler: Box<dyn Any>) {
-		let handler = *handler.downcast::<Box<dyn Fn(&Args) -> ()>>()
-			.expect("Cannot disconnect handler - invalid type");
-
-		let mut index: Option<usize> = None;
+	fn connect_any_with(&mut self, options: ConnectOptions, handler: Box<dyn Any>) -> Connection {
+		let handler_type_name = std::any::type_name_of_val(&handler);
 
-		for i in 0..self.handlers.len() {
-			let e_handler = &self.handlers[i];
-			// derefdereferencing a &Box<T> into T waowww
-			if (**e_handler).type_id() == (*handler).type_id() {
-				index = Some(i);
-				break;
-			}
-		}
+		let handler = *handler.downcast::<Box<dyn Fn(&Args) -> Propagation>>()
+			.unwrap_or_else(|_| panic!(
+				"Cannot connect handler - invalid type ({} required, got {} instead)",
+				std::any::type_name::<Box<dyn Fn(&Args) -> Propagation>>(),
+				handler_type_name
+			));
 
-		if index.is_none() {
-			log::warn!("Not removing handler {:?}: doesn't exist in the current signal", handler.type_id());
-		}
+		self.insert(options, Rc::from(handler))
+	}
 
-		self.handlers.remove(index.unwrap());
+	fn disconnect_any(&mut self, id: ConnectionId) -> bool {
+		self.disconnect(id)
 	}
 
-	fn emit_any(&self, args: &dyn Any) {
+	fn emit_any(&self, args: &dyn Any) -> Propagation {
 		let args = args.downcast_ref::<Args>()
 			.expect("Signal called with invalid arguments");
-		
-		for handler in &self.handlers {
-			handler.as_ref().call((args,));
-		}
+
+		emit_slots(&self.slots, args, &self.instrument)
+	}
+
+	fn snapshot_any(&self) -> Box<dyn Any> {
+		Box::new(self.snapshot())
+	}
+
+	#[cfg(feature = "instrumentation")]
+	fn stats(&self) -> crate::SignalStats {
+		StaticSignal::stats(self)
 	}
 
 	fn clear(&mut self) {
-		self.handlers.clear();
-		self.capture_handlers.clear();
-		self.captures.clear();
+		for slot in self.slots.drain(..) {
+			slot.kill();
+		}
+	}
+
+	fn args_type_id(&self) -> TypeId {
+		TypeId::of::<Args>()
+	}
+
+	fn args_type_name(&self) -> &'static str {
+		type_name::<Args>()
+	}
+}
+
+/// The handlers of a `StaticSignal` at some point in time, independent of where the signal itself lives.
+pub struct SignalSnapshot<Args: 'static> {
+	slots: Vec<Slot<Handler<Args>>>,
+	instrument: Instrument
+}
+
+impl<Args> SignalSnapshot<Args> where Args: 'static {
+	pub fn emit(&self, args: Args) -> Propagation {
+		emit_slots(&self.slots, &args, &self.instrument)
 	}
+
+	pub fn len(&self) -> usize { self.slots.len() }
+	pub fn is_empty(&self) -> bool { self.slots.is_empty() }
+}
+
+fn emit_slots<Args>(slots: &[Slot<Handler<Args>>], args: &Args, instrument: &Instrument) -> Propagation {
+	let _emit = instrument.emit(slots.len());
+
+	for slot in slots {
+		if !slot.begin_call() {
+			continue;
+		}
+
+		let propagation = {
+			let _handler = instrument.handler(slot.id);
+			slot.handler.as_ref().call((args,))
+		};
+
+		if propagation == Propagation::Stop {
+			return Propagation::Stop;
+		}
+	}
+
+	Propagation::Continue
 }
@@ -0,0 +1,53 @@
+use std::{any::Any, collections::HashMap, sync::{Arc, RwLock}};
+
+use crate::{Connection, ConnectionId, SignalSync, SyncSignal};
+
+/// `Send + Sync` version of `SignalDispatcher`. Signals can be created, connected and emitted
+/// through a shared reference from any thread.
+pub struct SyncSignalDispatcher {
+	signals: RwLock<HashMap<String, Arc<dyn SignalSync>>>
+}
+
+impl SyncSignalDispatcher {
+	pub fn new() -> Self {
+		Self {
+			signals: RwLock::new(HashMap::new())
+		}
+	}
+
+	pub fn create_signal<Args: Copy + 'static>(&self, name: &str) {
+		let signal = SyncSignal::<Args>::new();
+		self.signals.write().unwrap().insert(name.to_string(), Arc::new(signal));
+	}
+
+	pub fn has_signal(&self, name: &str) -> bool {
+		self.signals.read().unwrap().contains_key(name)
+	}
+
+	pub fn connect<Args: Copy + 'static, F: Fn(&Args) + Send + Sync + 'static>(&self, name: &str, handler: F) -> Connection {
+		let handler: Arc<dyn Fn(&Args) + Send + Sync> = Arc::new(handler);
+		self.signal(name).connect_any(Box::new(handler) as Box<dyn Any + Send>)
+	}
+
+	pub fn disconnect(&self, name: &str, id: impl Into<ConnectionId>) -> bool {
+		self.signal(name).disconnect_any(id.into())
+	}
+
+	pub fn emit<Args: Copy + 'static>(&self, name: &str, args: Args) {
+		self.signal(name).emit_any(&args);
+	}
+
+	// cloned out of the map so handlers can use the dispatcher without deadlocking
+	fn signal(&self, name: &str) -> Arc<dyn SignalSync> {
+		self.signals.read().unwrap()
+			.get(name)
+			.cloned()
+			.unwrap_or_else(|| panic!("No such signal: {}", name))
+	}
+}
+
+impl Default for SyncSignalDispatcher {
+	fn default() -> Self {
+		Self::new()
+	}
+}
@@ -0,0 +1,147 @@
+use std::{any::Any, sync::{Arc, RwLock}};
+
+use crate::{ConnectOptions, Connection, ConnectionId, Slot, WeakCapture};
+
+/// Thread-safe counterpart of `Signal`, every method takes `&self` so the signal can be shared
+/// behind an `Arc`.
+pub trait SignalSync: Send + Sync {
+	/// This function is DANGEROUS and should be used with CAUTION!!!
+	fn connect_any(&self, handler: Box<dyn Any + Send>) -> Connection;
+
+	fn disconnect_any(&self, id: ConnectionId) -> bool;
+
+	/// This function is DANGEROUS and should be used with CAUTION!!!
+	fn emit_any(&self, args: &dyn Any);
+
+	fn clear(&self);
+}
+
+type SyncHandler<Args> = Arc<dyn Fn(&Args) + Send + Sync>;
+
+/// A signal whose handlers are `Send + Sync`, so it can be connected to and emitted from any thread.
+/// Handlers run on the emitting thread.
+pub struct SyncSignal<Args: 'static> {
+	slots: RwLock<Vec<Slot<SyncHandler<Args>>>>
+}
+
+impl<Args> SyncSignal<Args> where Args: 'static {
+	pub fn new() -> Self {
+		Self {
+			slots: RwLock::new(Vec::new())
+		}
+	}
+
+	pub fn connect<F: Fn(&Args) + Send + Sync + 'static>(&self, handler: F) -> Connection {
+		self.connect_arc(Arc::new(handler))
+	}
+
+	/// Connects a handler that only holds `capture` weakly, see `StaticSignal::connect_weak`.
+	pub fn connect_weak<W: WeakCapture + Send + Sync, F: Fn(&W::Strong, &Args) + Send + Sync + 'static>(&self, capture: W, handler: F) -> Connection {
+		let connection = Connection::new();
+		let this = connection.clone();
+
+		let handler: SyncHandler<Args> = Arc::new(move |args| {
+			match capture.upgrade() {
+				Some(capture) => handler(&capture, args),
+				None => this.disconnect()
+			}
+		});
+
+		self.push_slot(Slot::with_connection(&connection, handler, ConnectOptions::default()));
+		connection
+	}
+
+	fn connect_arc(&self, handler: SyncHandler<Args>) -> Connection {
+		let (slot, connection) = Slot::new(handler, ConnectOptions::default());
+		self.push_slot(slot);
+		connection
+	}
+
+	fn push_slot(&self, slot: Slot<SyncHandler<Args>>) {
+		let mut slots = self.slots.write().unwrap();
+		slots.retain(|slot| slot.is_alive());
+		slots.push(slot);
+	}
+
+	/// Disconnects exactly the handler identified by `id`. Returns `false` if it isn't connected
+	/// to this signal.
+	pub fn disconnect(&self, id: impl Into<ConnectionId>) -> bool {
+		let id = id.into();
+		let mut slots = self.slots.write().unwrap();
+
+		if let Some(index) = slots.iter().position(|slot| slot.id == id) {
+			slots.remove(index).kill();
+			return true;
+		}
+
+		log::warn!("Not removing handler {:?}: doesn't exist in the current signal", id);
+		false
+	}
+
+	pub fn is_connected(&self, id: ConnectionId) -> bool {
+		self.slots.read().unwrap().iter().any(|slot| slot.id == id && slot.is_alive())
+	}
+
+	pub fn connection_count(&self) -> usize {
+		self.slots.read().unwrap().iter().filter(|slot| slot.is_alive()).count()
+	}
+
+	pub fn emit(&self, args: Args) {
+		for handler in self.snapshot() {
+			(handler.as_ref())(&args);
+		}
+	}
+
+	pub fn clear(&self) {
+		for slot in self.slots.write().unwrap().drain(..) {
+			slot.kill();
+		}
+	}
+
+	// the lock is released before any handler runs, so handlers may connect/disconnect/emit freely
+	fn snapshot(&self) -> Vec<SyncHandler<Args>> {
+		self.slots.read().unwrap()
+			.iter()
+			.filter(|slot| slot.is_alive())
+			.map(|slot| slot.handler.clone())
+			.collect()
+	}
+}
+
+impl<Args> Default for SyncSignal<Args> where Args: 'static {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<Args> SignalSync for SyncSignal<Args> where Args: 'static {
+	fn connect_any(&self, handler: Box<dyn Any + Send>) -> Connection {
+		let handler_type_name = std::any::type_name_of_val(&handler);
+
+		let handler = *handler.downcast::<SyncHandler<Args>>()
+			.unwrap_or_else(|_| panic!(
+				"Cannot connect handler - invalid type ({} required, got {} instead)",
+				std::any::type_name::<SyncHandler<Args>>(),
+				handler_type_name
+			));
+
+		self.connect_arc(handler)
+	}
+
+	fn disconnect_any(&self, id: ConnectionId) -> bool {
+		self.disconnect(id)
+	}
+
+	fn emit_any(&self, args: &dyn Any) {
+		let args = args.downcast_ref::<Args>()
+			.expect("Signal called with invalid arguments");
+
+		for handler in self.snapshot() {
+			(handler.as_ref())(args);
+		}
+	}
+
+	fn clear(&self) {
+		SyncSignal::clear(self);
+	}
+}
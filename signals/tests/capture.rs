@@ -0,0 +1,62 @@
+use std::{cell::{Cell, RefCell}, rc::Rc, sync::{Arc, atomic::{AtomicU32, Ordering}}};
+
+use fatum_signals::{StaticSignal, SyncSignal};
+
+#[test]
+fn weak_capture() {
+	let mut signal: StaticSignal<u32> = StaticSignal::new();
+	let log = Rc::new(RefCell::new(Vec::new()));
+
+	let connection = signal.connect_weak(Rc::downgrade(&log), |log, args| {
+		log.borrow_mut().push(*args);
+	});
+
+	signal.emit(1);
+	assert_eq!(*log.borrow(), vec![1]);
+
+	// the handler doesn't keep the log alive, and goes away with it
+	drop(log);
+	signal.emit(2);
+
+	assert!(!connection.is_connected());
+	assert_eq!(signal.connection_count(), 0);
+}
+
+#[test]
+fn multiple_captures() {
+	let mut signal: StaticSignal<u32> = StaticSignal::new();
+
+	let total = Rc::new(Cell::new(0));
+	let multiplier = Rc::new(Cell::new(2));
+
+	signal.connect_weak((Rc::downgrade(&total), Rc::downgrade(&multiplier)), |(total, multiplier), args| {
+		total.set(total.get() + args * multiplier.get());
+	});
+
+	signal.emit(3);
+	assert_eq!(total.get(), 6);
+
+	// losing any of the captures disconnects the handler
+	drop(multiplier);
+	signal.emit(3);
+
+	assert_eq!(total.get(), 6);
+	assert_eq!(signal.connection_count(), 0);
+}
+
+#[test]
+fn sync_weak_capture() {
+	let signal: SyncSignal<u32> = SyncSignal::new();
+	let total = Arc::new(AtomicU32::new(0));
+
+	signal.connect_weak(Arc::downgrade(&total), |total, args| {
+		total.fetch_add(*args, Ordering::SeqCst);
+	});
+
+	signal.emit(5);
+	assert_eq!(total.load(Ordering::SeqCst), 5);
+
+	drop(total);
+	signal.emit(5);
+	assert_eq!(signal.connection_count(), 0);
+}
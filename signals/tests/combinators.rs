@@ -0,0 +1,118 @@
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use fatum_signals::{Executor, SignalSource, StaticSignal};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AnyInput {
+	Key(u32),
+	Mouse(u8)
+}
+
+fn collect<T: Clone + 'static>(signal: &fatum_signals::SharedSignal<T>) -> Rc<RefCell<Vec<T>>> {
+	let received = Rc::new(RefCell::new(Vec::new()));
+
+	{
+		let received = received.clone();
+		signal.connect(move |args: &T| received.borrow_mut().push(args.clone()));
+	}
+
+	received
+}
+
+#[test]
+fn map_filter() {
+	let mut key_down: StaticSignal<u32> = StaticSignal::new();
+
+	let space = key_down.filter(|key| *key == 32);
+	let doubled = key_down.map(|key| key * 2);
+
+	let spaces = collect(&space);
+	let doubles = collect(&doubled);
+
+	for key in [32, 65, 32] {
+		key_down.emit(key);
+	}
+
+	assert_eq!(*spaces.borrow(), vec![32, 32]);
+	assert_eq!(*doubles.borrow(), vec![64, 130, 64]);
+
+	// the derived signals going away disconnects them from the source
+	drop(space);
+	drop(doubled);
+	key_down.emit(32);
+	assert_eq!(key_down.connection_count(), 0);
+}
+
+#[test]
+fn merge() {
+	let mut key_down: StaticSignal<u32> = StaticSignal::new();
+	let mut mouse_down: StaticSignal<u8> = StaticSignal::new();
+
+	let any_input = key_down.map(|key| AnyInput::Key(*key))
+		.merge(&mut mouse_down.map(|button| AnyInput::Mouse(*button)));
+
+	let received = collect(&any_input);
+
+	key_down.emit(65);
+	mouse_down.emit(1);
+
+	assert_eq!(*received.borrow(), vec![AnyInput::Key(65), AnyInput::Mouse(1)]);
+}
+
+#[test]
+fn throttle() {
+	let mut update: StaticSignal<u32> = StaticSignal::new();
+	let mut frames: StaticSignal<Duration> = StaticSignal::new();
+	let throttled = update.throttle(Duration::from_millis(100), &mut frames);
+	let received = collect(&throttled);
+
+	// 60 fps for half a second
+	for frame in 0..30 {
+		update.emit(frame);
+		frames.emit(Duration::from_micros(16_667));
+	}
+
+	assert_eq!(*received.borrow(), vec![0, 6, 12, 18, 24]);
+
+	// only the deltas it's given count, not the executor's clock
+	update.emit(30);
+	Executor::update(Duration::from_secs(1));
+	update.emit(31);
+	assert_eq!(*received.borrow(), vec![0, 6, 12, 18, 24, 30]);
+}
+
+#[test]
+fn debounce() {
+	let mut typed: StaticSignal<char> = StaticSignal::new();
+	let mut frames: StaticSignal<Duration> = StaticSignal::new();
+	let settled = typed.debounce(Duration::from_millis(300), &mut frames);
+	let received = collect(&settled);
+
+	for c in ['a', 'b', 'c'] {
+		typed.emit(c);
+		frames.emit(Duration::from_millis(100));
+	}
+
+	assert!(received.borrow().is_empty());
+
+	for _ in 0..4 {
+		frames.emit(Duration::from_millis(100));
+	}
+
+	assert_eq!(*received.borrow(), vec!['c']);
+}
+
+#[test]
+fn frames_from_update() {
+	// what a node's update looks like
+	let mut update: StaticSignal<(u32, Duration)> = StaticSignal::new();
+	let mut frames = update.map(|(_, delta)| *delta);
+	let ticks = update.map(|(frame, _)| *frame).throttle(Duration::from_millis(100), &mut frames);
+	let received = collect(&ticks);
+
+	for frame in 0..12 {
+		update.emit((frame, Duration::from_millis(50)));
+	}
+
+	assert_eq!(*received.borrow(), vec![0, 2, 4, 6, 8, 10]);
+}
@@ -0,0 +1,84 @@
+use std::{cell::Cell, rc::Rc};
+
+use fatum_signals::{SignalDispatcher, StaticSignal};
+
+#[test]
+fn disconnect_by_id() {
+	let mut signal: StaticSignal<u32> = StaticSignal::new();
+	let sum = Rc::new(Cell::new(0));
+
+	let first = {
+		let sum = sum.clone();
+		signal.connect(move |args| sum.set(sum.get() + args))
+	};
+
+	let second = {
+		let sum = sum.clone();
+		// the old type_id() lookup saw every handler as `dyn Fn` and always removed the first one
+		signal.connect(move |args| sum.set(sum.get() + args * 10))
+	};
+
+	signal.emit(1);
+	assert_eq!(sum.get(), 11);
+
+	assert!(signal.disconnect(second.id()));
+	assert!(!second.is_connected());
+	assert!(first.is_connected());
+
+	signal.emit(1);
+	assert_eq!(sum.get(), 12);
+
+	// disconnecting twice is not a panic
+	assert!(!signal.disconnect(second));
+}
+
+#[test]
+fn scoped_connection() {
+	let mut signal: StaticSignal<()> = StaticSignal::new();
+	let calls = Rc::new(Cell::new(0));
+
+	{
+		let calls = calls.clone();
+		let _guard = signal.connect(move |_| calls.set(calls.get() + 1)).scoped();
+
+		signal.emit(());
+		assert_eq!(signal.connection_count(), 1);
+	}
+
+	signal.emit(());
+	assert_eq!(calls.get(), 1);
+	assert_eq!(signal.connection_count(), 0);
+
+	let released = {
+		let calls = calls.clone();
+		signal.connect(move |_| calls.set(calls.get() + 1)).scoped().release()
+	};
+
+	signal.emit(());
+	assert_eq!(calls.get(), 2);
+
+	released.disconnect();
+	signal.emit(());
+	assert_eq!(calls.get(), 2);
+}
+
+#[test]
+fn dispatcher_disconnect() {
+	let mut dispatcher = SignalDispatcher::new();
+	dispatcher.create_signal::<u32>("value");
+
+	let received = Rc::new(Cell::new(0));
+
+	let connection = {
+		let received = received.clone();
+		dispatcher.connect("value", move |args: &u32| received.set(*args))
+	};
+
+	dispatcher.emit("value", 4u32);
+	assert_eq!(received.get(), 4);
+
+	assert!(dispatcher.disconnect("value", connection));
+
+	dispatcher.emit("value", 8u32);
+	assert_eq!(received.get(), 4);
+}
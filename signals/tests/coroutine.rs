@@ -0,0 +1,107 @@
+use std::{cell::{Cell, RefCell}, rc::Rc, time::Duration};
+
+use fatum_signals::{Executor, StaticSignal};
+
+const FRAME: Duration = Duration::from_millis(500);
+
+#[test]
+fn sequential_logic() {
+	let ready: Rc<RefCell<StaticSignal<()>>> = Rc::new(RefCell::new(StaticSignal::new()));
+	let pressed: Rc<RefCell<StaticSignal<u32>>> = Rc::new(RefCell::new(StaticSignal::new()));
+	let step = Rc::new(Cell::new(0));
+
+	{
+		let ready = ready.clone();
+		let pressed = pressed.clone();
+		let step = step.clone();
+
+		Executor::spawn(async move {
+			let next = ready.borrow_mut().next();
+			next.await;
+			step.set(1);
+
+			Executor::sleep(Duration::from_secs(2)).await;
+			step.set(2);
+
+			let next = pressed.borrow_mut().next();
+			let button = next.await;
+			step.set(10 + button);
+		});
+	}
+
+	Executor::update(FRAME);
+	assert_eq!(step.get(), 0);
+
+	ready.borrow().emit(());
+	Executor::update(FRAME);
+	assert_eq!(step.get(), 1);
+
+	// 2 seconds of frame time, counted from the frame the sleep started on
+	for _ in 0..3 {
+		Executor::update(FRAME);
+		assert_eq!(step.get(), 1);
+	}
+
+	Executor::update(FRAME);
+	assert_eq!(step.get(), 2);
+
+	pressed.borrow().emit(3);
+	Executor::update(FRAME);
+	assert_eq!(step.get(), 13);
+	assert_eq!(Executor::task_count(), 0);
+}
+
+#[test]
+fn cancel() {
+	let mut signal: StaticSignal<()> = StaticSignal::new();
+	let done = Rc::new(Cell::new(false));
+
+	let next = signal.next();
+	assert_eq!(signal.connection_count(), 1);
+
+	let task = {
+		let done = done.clone();
+
+		Executor::spawn(async move {
+			next.await;
+			done.set(true);
+		})
+	};
+
+	Executor::update(FRAME);
+	task.cancel();
+
+	// the pending future was dropped along with the task
+	assert_eq!(signal.connection_count(), 0);
+
+	signal.emit(());
+	Executor::update(FRAME);
+
+	assert!(!done.get());
+	assert!(task.is_cancelled());
+	assert!(!task.is_finished());
+}
+
+#[test]
+fn next_frame() {
+	let frames = Rc::new(Cell::new(0));
+
+	let task = {
+		let frames = frames.clone();
+
+		Executor::spawn(async move {
+			for _ in 0..3 {
+				frames.set(frames.get() + 1);
+				Executor::next_frame().await;
+			}
+		})
+	};
+
+	for expected in 1..=3 {
+		Executor::update(FRAME);
+		assert_eq!(frames.get(), expected);
+	}
+
+	Executor::update(FRAME);
+	assert!(task.is_finished());
+}
@@ -0,0 +1,91 @@
+use std::{cell::{Cell, RefCell}, rc::Rc};
+
+use fatum_signals::{SignalDispatcher, SignalQueue, StaticSignal};
+
+#[test]
+fn deferred_until_flush() {
+	let mut signal: StaticSignal<u32> = StaticSignal::new();
+	let received = Rc::new(Cell::new(0));
+
+	{
+		let received = received.clone();
+		signal.connect(move |args| received.set(received.get() + args));
+	}
+
+	signal.emit_deferred(2);
+	signal.emit_deferred(3);
+	assert_eq!(received.get(), 0);
+	assert_eq!(SignalQueue::len(), 2);
+
+	// the signal can move around before the flush
+	let moved = signal;
+
+	assert_eq!(SignalQueue::flush(), 2);
+	assert_eq!(received.get(), 5);
+	assert!(SignalQueue::is_empty());
+
+	drop(moved);
+}
+
+#[test]
+fn disconnected_before_flush() {
+	let mut signal: StaticSignal<()> = StaticSignal::new();
+	let calls = Rc::new(Cell::new(0));
+
+	let connection = {
+		let calls = calls.clone();
+		signal.connect(move |_| calls.set(calls.get() + 1))
+	};
+
+	signal.emit_deferred(());
+	connection.disconnect();
+
+	SignalQueue::flush();
+	assert_eq!(calls.get(), 0);
+}
+
+#[test]
+fn queued_during_flush() {
+	let echo = Rc::new(RefCell::new(StaticSignal::<u32>::new()));
+	let last = Rc::new(Cell::new(0));
+
+	{
+		let last = last.clone();
+		echo.borrow_mut().connect(move |args| last.set(*args));
+	}
+
+	let mut signal: StaticSignal<u32> = StaticSignal::new();
+
+	{
+		let echo = echo.clone();
+		signal.connect(move |args| echo.borrow().emit_deferred(args + 1));
+	}
+
+	signal.emit_deferred(1);
+
+	// whatever a handler queues only runs on the next flush
+	assert_eq!(SignalQueue::flush(), 1);
+	assert_eq!(last.get(), 0);
+
+	assert_eq!(SignalQueue::flush(), 1);
+	assert_eq!(last.get(), 2);
+}
+
+#[test]
+fn dispatcher_deferred() {
+	let mut dispatcher = SignalDispatcher::new();
+	dispatcher.create_signal::<u32>("tick");
+
+	let ticks = Rc::new(Cell::new(0));
+
+	{
+		let ticks = ticks.clone();
+		dispatcher.connect("tick", move |args: &u32| ticks.set(*args));
+	}
+
+	dispatcher.emit_deferred("tick", 4u32);
+	assert_eq!(ticks.get(), 0);
+
+	SignalQueue::flush();
+	assert_eq!(ticks.get(), 4);
+}
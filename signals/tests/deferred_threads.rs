@@ -0,0 +1,39 @@
+// Separate binary, the flushing thread is global and would affect the other deferred tests.
+use std::{sync::{Arc, atomic::{AtomicU32, Ordering}}, thread};
+
+use fatum_signals::{SignalQueue, StaticSignal};
+
+#[test]
+fn emitted_immediately_off_the_flushing_thread() {
+	assert!(SignalQueue::set_flushing_thread());
+	assert!(SignalQueue::set_flushing_thread());
+
+	let received = Arc::new(AtomicU32::new(0));
+
+	{
+		let received = received.clone();
+		thread::spawn(move || {
+			assert!(!SignalQueue::set_flushing_thread());
+
+			let mut signal: StaticSignal<u32> = StaticSignal::new();
+			signal.connect(move |args| { received.fetch_add(*args, Ordering::Relaxed); });
+
+			signal.emit_deferred(3);
+			assert!(SignalQueue::is_empty());
+		}).join().unwrap();
+	}
+
+	assert_eq!(received.load(Ordering::Relaxed), 3);
+
+	// still deferred on the flushing thread
+	let mut signal: StaticSignal<u32> = StaticSignal::new();
+	{
+		let received = received.clone();
+		signal.connect(move |args| { received.fetch_add(*args, Ordering::Relaxed); });
+	}
+
+	signal.emit_deferred(2);
+	assert_eq!(received.load(Ordering::Relaxed), 3);
+	assert_eq!(SignalQueue::flush(), 1);
+	assert_eq!(received.load(Ordering::Relaxed), 5);
+}
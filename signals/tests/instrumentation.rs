@@ -0,0 +1,51 @@
+#![cfg(feature = "instrumentation")]
+
+use std::time::Duration;
+
+use fatum_signals::{Propagation, SignalDispatcher, StaticSignal};
+
+#[test]
+fn signal_stats() {
+	let mut signal: StaticSignal<u32> = StaticSignal::with_name("key_down");
+
+	let slow = signal.connect(|_| std::thread::sleep(Duration::from_millis(2)));
+	let consumer = signal.connect_priority(-1, |args| {
+		if *args == 0 { Propagation::Stop } else { Propagation::Continue }
+	});
+	let skipped = signal.connect_priority(-2, |_| Propagation::Continue);
+
+	signal.emit(1);
+	signal.emit(0);
+
+	let stats = signal.stats();
+	assert_eq!(stats.emits, 2);
+	assert_eq!(stats.handler_calls, 5);
+
+	assert_eq!(stats.handlers[&slow.id()].calls, 2);
+	assert!(stats.handlers[&slow.id()].max_time >= Duration::from_millis(2));
+	assert_eq!(stats.handlers[&consumer.id()].calls, 2);
+	assert_eq!(stats.handlers[&skipped.id()].calls, 1);
+
+	signal.reset_stats();
+	assert_eq!(signal.stats().emits, 0);
+}
+
+#[test]
+fn dispatcher_stats() {
+	let mut dispatcher = SignalDispatcher::new();
+	dispatcher.create_signal::<()>("ready");
+	dispatcher.create_signal::<()>("update");
+
+	dispatcher.connect("update", |_: &()| ());
+
+	for _ in 0..3 {
+		dispatcher.emit("update", ());
+	}
+
+	assert_eq!(dispatcher.stats("update").unwrap().handler_calls, 3);
+	assert_eq!(dispatcher.stats("missing"), None);
+
+	let all = dispatcher.all_stats();
+	assert_eq!(all["ready"].emits, 0);
+	assert_eq!(all["update"].emits, 3);
+}
@@ -0,0 +1,37 @@
+use std::{cell::Cell, rc::Rc};
+
+use fatum_signals::{SignalDispatcher, SignalKey};
+
+const SCORE_CHANGED: SignalKey<u32> = SignalKey::new("score_changed");
+const GAME_OVER: SignalKey<()> = SignalKey::new("game_over");
+
+#[test]
+fn typed_keys() {
+	let mut dispatcher = SignalDispatcher::new();
+	let score = Rc::new(Cell::new(0));
+
+	// nothing connected yet, nothing to call
+	dispatcher.emit_key(GAME_OVER, ());
+
+	{
+		let score = score.clone();
+		dispatcher.connect_key(SCORE_CHANGED, move |args| score.set(*args));
+	}
+
+	dispatcher.emit_key(SCORE_CHANGED, 100);
+	assert_eq!(score.get(), 100);
+
+	// the string API still reaches the same signal
+	dispatcher.emit("score_changed", 200u32);
+	assert_eq!(score.get(), 200);
+}
+
+#[test]
+#[should_panic(expected = "Signal score_changed takes u32, not i64")]
+fn mismatched_key() {
+	const SCORE_CHANGED_I64: SignalKey<i64> = SignalKey::new("score_changed");
+
+	let mut dispatcher = SignalDispatcher::new();
+	dispatcher.connect_key(SCORE_CHANGED, |_| {});
+	dispatcher.connect_key(SCORE_CHANGED_I64, |_| {});
+}
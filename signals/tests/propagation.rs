@@ -0,0 +1,86 @@
+use std::{cell::{Cell, RefCell}, rc::Rc};
+
+use fatum_signals::{ConnectOptions, Propagation, SignalDispatcher, StaticSignal};
+
+#[test]
+fn priority_order() {
+	let mut signal: StaticSignal<()> = StaticSignal::new();
+	let order = Rc::new(RefCell::new(Vec::new()));
+
+	for (name, priority) in [("gameplay", 0), ("ui", 10), ("debug", -5), ("gameplay2", 0)] {
+		let order = order.clone();
+
+		signal.connect_priority(priority, move |_| {
+			order.borrow_mut().push(name);
+			Propagation::Continue
+		});
+	}
+
+	assert_eq!(signal.emit(()), Propagation::Continue);
+	assert_eq!(*order.borrow(), vec!["ui", "gameplay", "gameplay2", "debug"]);
+}
+
+#[test]
+fn stop_propagation() {
+	let mut clicked: StaticSignal<(f32, f32)> = StaticSignal::new();
+	let gameplay_clicks = Rc::new(Cell::new(0));
+
+	// the UI consumes clicks inside its panel
+	clicked.connect_priority(100, |args| {
+		if args.0 < 200.0 {
+			Propagation::Stop
+		} else {
+			Propagation::Continue
+		}
+	});
+
+	{
+		let gameplay_clicks = gameplay_clicks.clone();
+		clicked.connect(move |_| gameplay_clicks.set(gameplay_clicks.get() + 1));
+	}
+
+	assert_eq!(clicked.emit((50.0, 50.0)), Propagation::Stop);
+	assert_eq!(gameplay_clicks.get(), 0);
+
+	assert_eq!(clicked.emit((500.0, 50.0)), Propagation::Continue);
+	assert_eq!(gameplay_clicks.get(), 1);
+}
+
+#[test]
+fn once() {
+	let mut signal: StaticSignal<u32> = StaticSignal::new();
+	let calls = Rc::new(Cell::new(0));
+
+	let connection = {
+		let calls = calls.clone();
+		signal.connect_once(move |_| calls.set(calls.get() + 1))
+	};
+
+	signal.emit(1);
+	signal.emit(2);
+
+	assert_eq!(calls.get(), 1);
+	assert!(!connection.is_connected());
+	assert_eq!(signal.connection_count(), 0);
+}
+
+#[test]
+fn dispatcher_options() {
+	let mut dispatcher = SignalDispatcher::new();
+	dispatcher.create_signal::<u32>("hit");
+
+	let last = Rc::new(Cell::new(0));
+
+	{
+		let last = last.clone();
+		dispatcher.connect("hit", move |args: &u32| last.set(*args));
+	}
+
+	dispatcher.connect_with("hit", ConnectOptions { priority: 1, once: true }, |_: &u32| Propagation::Stop);
+
+	assert_eq!(dispatcher.emit("hit", 1u32), Propagation::Stop);
+	assert_eq!(last.get(), 0);
+
+	assert_eq!(dispatcher.emit("hit", 2u32), Propagation::Continue);
+	assert_eq!(last.get(), 2);
+}
@@ -0,0 +1,55 @@
+use std::{sync::{Arc, atomic::{AtomicU64, Ordering}}, thread};
+
+use fatum_signals::{SyncSignal, SyncSignalDispatcher};
+
+#[test]
+fn emit_from_threads() {
+	let signal: Arc<SyncSignal<u64>> = Arc::new(SyncSignal::new());
+	let sum = Arc::new(AtomicU64::new(0));
+
+	{
+		let sum = sum.clone();
+		signal.connect(move |args| {
+			sum.fetch_add(*args, Ordering::SeqCst);
+		});
+	}
+
+	let threads: Vec<_> = (1..=4)
+		.map(|i| {
+			let signal = signal.clone();
+			thread::spawn(move || signal.emit(i))
+		})
+		.collect();
+
+	for thread in threads {
+		thread.join().unwrap();
+	}
+
+	assert_eq!(sum.load(Ordering::SeqCst), 10);
+}
+
+#[test]
+fn dispatcher_across_threads() {
+	let dispatcher = Arc::new(SyncSignalDispatcher::new());
+	dispatcher.create_signal::<u64>("loaded");
+
+	let loaded = Arc::new(AtomicU64::new(0));
+
+	let connection = {
+		let loaded = loaded.clone();
+		dispatcher.connect("loaded", move |args: &u64| {
+			loaded.fetch_add(*args, Ordering::SeqCst);
+		})
+	};
+
+	{
+		let dispatcher = dispatcher.clone();
+		thread::spawn(move || dispatcher.emit("loaded", 3u64)).join().unwrap();
+	}
+
+	assert_eq!(loaded.load(Ordering::SeqCst), 3);
+
+	assert!(dispatcher.disconnect("loaded", connection));
+	dispatcher.emit("loaded", 3u64);
+	assert_eq!(loaded.load(Ordering::SeqCst), 3);
+}